@@ -0,0 +1,242 @@
+//! Mini-batch iteration over images and one-hot labels.
+//!
+//! ```no_run
+//! use datasets::batches::Batches;
+//! use datasets::cifar10;
+//! use datasets::transforms::CutMix;
+//!
+//! let data = cifar10::new_normalized();
+//! for epoch in 0..10 {
+//!     let batches = Batches::new(&data.trn_img, &data.trn_lbl, 128, epoch)
+//!         .batch_transform(CutMix::new(1.));
+//!     for (images, soft_labels) in batches {
+//!         assert_eq!(images.shape()[1..], [3, 32, 32]);
+//!     }
+//! }
+//! ```
+
+use ndarray::prelude::*;
+use ndarray::RemoveAxis;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::transforms::BatchTransform;
+
+/// Iterates over `(images, labels)` batches of `batch_size` samples.
+///
+/// `images` can be any array whose first axis indexes samples, e.g. the NHW images of
+/// `mnist_builder::Data` or the NCHW images of `cifar_builder::Data`. Samples are shuffled
+/// with `seed` unless disabled with `shuffle(false)`. Batch transforms see every batch as
+/// NCHW, NHW batches are treated as single-channel. Rows and columns have to be the last two axes:
+/// `batch_transform` rejects `Layout::Flat` images, and `CutMix` would cut `Layout::Nhwc`
+/// images along the wrong axes.
+pub struct Batches<'a, D: Dimension> {
+    images: ArrayView<'a, f32, D>,
+    labels: ArrayView2<'a, f32>,
+    batch_size: usize,
+    order: Vec<usize>,
+    pos: usize,
+    drop_last: bool,
+    transform: Option<Box<dyn BatchTransform + 'a>>,
+    rng: StdRng,
+}
+
+impl<'a, D: Dimension + RemoveAxis> Batches<'a, D> {
+    pub fn new<S, T>(
+        images: &'a ArrayBase<S, D>,
+        labels: &'a ArrayBase<T, Ix2>,
+        batch_size: usize,
+        seed: u64,
+    ) -> Batches<'a, D>
+    where
+        S: ndarray::Data<Elem = f32>,
+        T: ndarray::Data<Elem = f32>,
+    {
+        assert!(batch_size > 0, "Batch size must be positive.");
+        assert!(
+            images.len_of(Axis(0)) == labels.nrows(),
+            "Got {} images but {} labels.",
+            images.len_of(Axis(0)),
+            labels.nrows()
+        );
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut order: Vec<usize> = (0..labels.nrows()).collect();
+        order.shuffle(&mut rng);
+        Batches {
+            images: images.view(),
+            labels: labels.view(),
+            batch_size,
+            order,
+            pos: 0,
+            drop_last: false,
+            transform: None,
+            rng,
+        }
+    }
+
+    /// Visit the samples in random (default) or dataset order.
+    pub fn shuffle(mut self, shuffle: bool) -> Batches<'a, D> {
+        if !shuffle {
+            self.order.sort_unstable();
+        }
+        self
+    }
+
+    /// Skip the last batch if it has fewer than `batch_size` samples.
+    pub fn drop_last(mut self, drop_last: bool) -> Batches<'a, D> {
+        self.drop_last = drop_last;
+        self
+    }
+
+    /// Apply e.g. `MixUp` or `CutMix` to every batch.
+    pub fn batch_transform<T: BatchTransform + 'a>(mut self, transform: T) -> Batches<'a, D> {
+        assert!(
+            self.images.ndim() >= 3,
+            "Batch transforms need images with rows and columns, got shape {:?}.",
+            self.images.shape()
+        );
+        self.transform = Some(Box::new(transform));
+        self
+    }
+
+    /// Number of remaining batches.
+    pub fn num_batches(&self) -> usize {
+        let remaining = self.order.len() - self.pos;
+        if self.drop_last {
+            remaining / self.batch_size
+        } else {
+            (remaining + self.batch_size - 1) / self.batch_size
+        }
+    }
+}
+
+impl<'a, D: Dimension + RemoveAxis> Iterator for Batches<'a, D> {
+    type Item = (Array<f32, D>, Array2<f32>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let end = (self.pos + self.batch_size).min(self.order.len());
+        if end == self.pos || (self.drop_last && end - self.pos < self.batch_size) {
+            return None;
+        }
+        let indices = &self.order[self.pos..end];
+        self.pos = end;
+        let images = self.images.select(Axis(0), indices);
+        let labels = self.labels.select(Axis(0), indices);
+        match &self.transform {
+            None => Some((images, labels)),
+            Some(transform) => {
+                // View the batch as NCHW, treating all axes between the first and the last two
+                // as channels.
+                let dim = images.raw_dim();
+                let shape = images.shape();
+                let ndim = shape.len();
+                let channels = shape[1..ndim - 2].iter().product();
+                let nchw = (shape[0], channels, shape[ndim - 2], shape[ndim - 1]);
+                let images = images.into_shape(nchw).unwrap();
+                let (images, labels) = transform.apply_batch(images, labels, &mut self.rng);
+                Some((images.into_shape(dim).unwrap(), labels))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::{CutMix, MixUp};
+
+    /// 10 NHW images, image `i` is filled with `i` and has the one-hot label `i % 4`.
+    fn data() -> (Array3<f32>, Array2<f32>) {
+        let images = Array3::from_shape_fn((10, 3, 3), |(n, _, _)| n as f32);
+        let labels = Array2::from_shape_fn((10, 4), |(n, c)| (n % 4 == c) as u8 as f32);
+        (images, labels)
+    }
+
+    /// Index of every image of a batch, recovered from its pixels.
+    fn ids(images: &Array3<f32>) -> Vec<usize> {
+        images
+            .outer_iter()
+            .map(|image| image[[0, 0]] as usize)
+            .collect()
+    }
+
+    #[test]
+    fn visits_every_sample_once() {
+        let (images, labels) = data();
+        let batches = Batches::new(&images, &labels, 4, 0);
+        assert_eq!(batches.num_batches(), 3);
+        let mut seen = Vec::new();
+        let mut sizes = Vec::new();
+        for (batch, batch_labels) in batches {
+            for (&id, label) in ids(&batch).iter().zip(batch_labels.outer_iter()) {
+                assert_eq!(label, labels.row(id));
+            }
+            sizes.push(batch.len_of(Axis(0)));
+            seen.extend(ids(&batch));
+        }
+        assert_eq!(sizes, vec![4, 4, 2]);
+        assert_ne!(seen, (0..10).collect::<Vec<_>>());
+        seen.sort_unstable();
+        assert_eq!(seen, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn order_and_last_batch() {
+        let (images, labels) = data();
+        let ordered: Vec<usize> = Batches::new(&images, &labels, 3, 5)
+            .shuffle(false)
+            .flat_map(|(batch, _)| ids(&batch))
+            .collect();
+        assert_eq!(ordered, (0..10).collect::<Vec<_>>());
+
+        let order = |seed| -> Vec<usize> {
+            Batches::new(&images, &labels, 3, seed)
+                .flat_map(|(batch, _)| ids(&batch))
+                .collect()
+        };
+        assert_eq!(order(1), order(1));
+        assert_ne!(order(1), order(2));
+
+        let batches = Batches::new(&images, &labels, 3, 0).drop_last(true);
+        assert_eq!(batches.num_batches(), 3);
+        assert!(batches
+            .map(|(batch, _)| batch.len_of(Axis(0)))
+            .all(|n| n == 3));
+    }
+
+    #[test]
+    fn batch_transforms() {
+        let (images, labels) = data();
+        let mixed = |seed| -> Vec<(Array3<f32>, Array2<f32>)> {
+            Batches::new(&images, &labels, 4, seed)
+                .batch_transform(MixUp::new(1.))
+                .collect()
+        };
+        let batches = mixed(3);
+        assert_eq!(batches, mixed(3));
+        assert_ne!(batches, mixed(4));
+        for (batch, soft) in &batches {
+            assert_eq!(batch.shape()[1..], [3, 3]);
+            assert!(soft
+                .outer_iter()
+                .all(|label| (label.sum() - 1.).abs() < 1e-6));
+        }
+
+        let nchw = images.clone().insert_axis(Axis(1));
+        for (batch, soft) in Batches::new(&nchw, &labels, 5, 0).batch_transform(CutMix::new(1.)) {
+            assert_eq!(batch.shape()[1..], [1, 3, 3]);
+            assert!(soft
+                .outer_iter()
+                .all(|label| (label.sum() - 1.).abs() < 1e-6));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Batch transforms need images with rows and columns")]
+    fn rejects_transforms_of_flat_images() {
+        let (images, labels) = data();
+        let flat = images.into_shape((10, 9)).unwrap();
+        let _ = Batches::new(&flat, &labels, 4, 0).batch_transform(MixUp::new(1.));
+    }
+}
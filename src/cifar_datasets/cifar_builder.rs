@@ -1,20 +1,64 @@
 use ndarray::prelude::*;
-use ndarray::{Array2, Array4};
+use ndarray::{Array2, Array4, Ix4, IxDyn, RemoveAxis};
 
 use std::error::Error;
 use std::fs::File;
 use std::io::prelude::*;
+use std::path::Path;
 
 #[cfg(feature = "download")]
 use super::download;
+use crate::dataset::{self, Dataset};
+use crate::layout::Layout;
+use crate::pickle;
 
-pub struct Data {
-    pub trn_img: Array4<f32>,
+/// Images in NCHW layout unless loaded with `new_with_layout`, which gives `Data<IxDyn>`.
+pub struct Data<D: Dimension = Ix4> {
+    pub trn_img: Array<f32, D>,
     pub trn_lbl: Array2<f32>,
-    pub tst_img: Array4<f32>,
+    pub tst_img: Array<f32, D>,
     pub tst_lbl: Array2<f32>,
 }
 
+impl<D: Dimension + RemoveAxis> Dataset for Data<D> {
+    type Dim = D;
+
+    fn from_parts(
+        trn_img: Array<f32, D>,
+        trn_lbl: Array2<f32>,
+        tst_img: Array<f32, D>,
+        tst_lbl: Array2<f32>,
+    ) -> Data<D> {
+        Data {
+            trn_img,
+            trn_lbl,
+            tst_img,
+            tst_lbl,
+        }
+    }
+
+    fn trn(&self) -> (&Array<f32, D>, &Array2<f32>) {
+        (&self.trn_img, &self.trn_lbl)
+    }
+
+    fn tst(&self) -> (&Array<f32, D>, &Array2<f32>) {
+        (&self.tst_img, &self.tst_lbl)
+    }
+}
+
+impl Data {
+    /// Load a dataset stored by `save_npz`.
+    pub fn load_npz<P: AsRef<Path>>(path: P) -> Result<Data, String> {
+        let (trn_img, trn_lbl, tst_img, tst_lbl) = dataset::read_npz(path)?;
+        Ok(Data {
+            trn_img,
+            trn_lbl,
+            tst_img,
+            tst_lbl,
+        })
+    }
+}
+
 fn read_into_buffer(bin_paths: Vec<&str>, base_path: &str) -> Result<Vec<u8>, Box<dyn Error>> {
     let mut buffer: Vec<u8> = Vec::new();
     for bin in &bin_paths {
@@ -31,74 +75,235 @@ fn read_into_buffer(bin_paths: Vec<&str>, base_path: &str) -> Result<Vec<u8>, Bo
     Ok(buffer)
 }
 
+/// File names and record layout, which differ between CIFAR-10 and CIFAR-100.
+struct Variant {
+    bin_paths_trn: &'static [&'static str],
+    bin_paths_tst: &'static [&'static str],
+    pickle_paths_trn: &'static [&'static str],
+    pickle_paths_tst: &'static [&'static str],
+    // CIFAR-100 records start with the coarse label followed by the fine label.
+    label_bytes: usize,
+    label_key: &'static str,
+    classes: usize,
+}
+
+static CIFAR10: Variant = Variant {
+    bin_paths_trn: &[
+        "data_batch_1.bin",
+        "data_batch_2.bin",
+        "data_batch_3.bin",
+        "data_batch_4.bin",
+        "data_batch_5.bin",
+    ],
+    bin_paths_tst: &["test_batch.bin"],
+    pickle_paths_trn: &[
+        "data_batch_1",
+        "data_batch_2",
+        "data_batch_3",
+        "data_batch_4",
+        "data_batch_5",
+    ],
+    pickle_paths_tst: &["test_batch"],
+    label_bytes: 1,
+    label_key: "labels",
+    classes: 10,
+};
+
+static CIFAR100: Variant = Variant {
+    bin_paths_trn: &["train.bin"],
+    bin_paths_tst: &["test.bin"],
+    pickle_paths_trn: &["train"],
+    pickle_paths_tst: &["test"],
+    label_bytes: 2,
+    label_key: "fine_labels",
+    classes: 100,
+};
+
 fn buffer2data(
     buffer: Vec<u8>,
     num_records: usize,
+    label_bytes: usize,
+    classes: usize,
 ) -> Result<(Array4<u8>, Array2<f32>), Box<dyn Error>> {
-    let mut labels: Array2<f32> = Array2::zeros((num_records, 10));
-    labels[[0, buffer[0] as usize]] = 1.;
+    let mut labels: Array2<f32> = Array2::zeros((num_records, classes));
     let mut data: Vec<u8> = Vec::with_capacity(num_records * 3072);
 
     for num in 0..num_records {
         // println!("Through image #{}/{}", num, num_records);
-        let base = num * (3073);
-        let label = buffer[base];
-        if label > 9 {
-            panic!(format!(
-                "Label is {}, which is inconsistent with the CIFAR-10 scheme",
-                label
-            ));
+        let base = num * (3072 + label_bytes);
+        let label = buffer[base + label_bytes - 1];
+        if label as usize >= classes {
+            panic!(
+                "Label is {}, which is inconsistent with the CIFAR-{} scheme",
+                label, classes
+            );
         }
         labels[[num, label as usize]] = 1.;
-        data.extend(&buffer[base + 1..=base + 3072]);
+        data.extend(&buffer[base + label_bytes..base + label_bytes + 3072]);
     }
     let data: Array4<u8> = Array::from_shape_vec((num_records, 3, 32, 32), data)?;
 
     Ok((data, labels))
 }
 
-fn get_dataset(base_path: &str, normalized: bool) -> Data {
-    let bin_paths_trn = vec![
-        "data_batch_1.bin",
-        "data_batch_2.bin",
-        "data_batch_3.bin",
-        "data_batch_4.bin",
-        "data_batch_5.bin",
-    ];
-    let bin_paths_tst = vec!["test_batch.bin"];
-    let num_records_trn = 50_000;
-    let num_records_tst = 10_000;
+/// Read the `data` array and the labels stored under `label_key` from pickled batches of the
+/// python distribution, which Downsampled ImageNet shares.
+pub(crate) fn read_pickle_batches(
+    pickle_paths: &[&str],
+    base_path: &str,
+    label_key: &str,
+) -> Result<(Vec<u8>, Vec<i64>), Box<dyn Error>> {
+    let mut data: Vec<u8> = Vec::new();
+    let mut labels: Vec<i64> = Vec::new();
+    for batch in pickle_paths {
+        let full_path = [base_path, batch].join("");
+        println!("{}", full_path);
 
-    println!("{}", base_path);
-    let buffer_trn = read_into_buffer(bin_paths_trn, base_path).unwrap();
-    let buffer_tst = read_into_buffer(bin_paths_tst, base_path).unwrap();
-    //println!("- Done parsing binary files to Vec<u8>");
+        let batch = pickle::load(Path::new(&full_path))?;
+        let batch_data = batch
+            .get("data")
+            .ok_or_else(|| format!("No data in {}", full_path))?
+            .to_ndarray()?
+            .to_u8()?;
+        data.extend(batch_data.iter());
+        let batch_labels = batch
+            .get(label_key)
+            .ok_or_else(|| format!("No {} in {}", label_key, full_path))?;
+        labels.extend(batch_labels.to_int_vec()?);
+    }
+    Ok((data, labels))
+}
 
-    let (trn_img, trn_lbl) = buffer2data(buffer_trn, num_records_trn).unwrap();
-    let (tst_img, tst_lbl) = buffer2data(buffer_tst, num_records_tst).unwrap();
+fn pickle2data(
+    pickle_paths: &[&str],
+    base_path: &str,
+    variant: &Variant,
+) -> Result<(Array4<u8>, Array2<f32>), Box<dyn Error>> {
+    let (data, labels) = read_pickle_batches(pickle_paths, base_path, variant.label_key)?;
+    let mut one_hot: Array2<f32> = Array2::zeros((labels.len(), variant.classes));
+    for (num, &label) in labels.iter().enumerate() {
+        if label < 0 || label as usize >= variant.classes {
+            return Err(format!(
+                "Label is {}, which is inconsistent with the CIFAR-{} scheme",
+                label, variant.classes
+            )
+            .into());
+        }
+        one_hot[[num, label as usize]] = 1.;
+    }
+    let data: Array4<u8> = Array::from_shape_vec((labels.len(), 3, 32, 32), data)?;
+    Ok((data, one_hot))
+}
+
+/// Raw NCHW bytes and one-hot labels of one split.
+type RawSplit = (Array4<u8>, Array2<f32>);
+
+/// Load the binary distribution from `base_path`, falling back to the python distribution in
+/// `python_base_path` if only that one is available. Images are returned as raw NCHW bytes.
+fn get_raw_dataset(
+    base_path: &str,
+    python_base_path: &str,
+    variant: &Variant,
+) -> (RawSplit, RawSplit) {
+    let num_records_trn = 50_000;
+    let num_records_tst = 10_000;
 
-    let mut trn_img = trn_img.mapv(|x| x as f32);
-    let mut tst_img = tst_img.mapv(|x| x as f32);
-    if normalized {
-        trn_img.mapv_inplace(|x| x / 256.);
-        tst_img.mapv_inplace(|x| x / 256.);
+    if !Path::new(base_path).exists() && Path::new(python_base_path).exists() {
+        println!("{}", python_base_path);
+        (
+            pickle2data(variant.pickle_paths_trn, python_base_path, variant).unwrap(),
+            pickle2data(variant.pickle_paths_tst, python_base_path, variant).unwrap(),
+        )
+    } else {
+        println!("{}", base_path);
+        let buffer_trn = read_into_buffer(variant.bin_paths_trn.to_vec(), base_path).unwrap();
+        let buffer_tst = read_into_buffer(variant.bin_paths_tst.to_vec(), base_path).unwrap();
+        //println!("- Done parsing binary files to Vec<u8>");
+        (
+            buffer2data(
+                buffer_trn,
+                num_records_trn,
+                variant.label_bytes,
+                variant.classes,
+            )
+            .unwrap(),
+            buffer2data(
+                buffer_tst,
+                num_records_tst,
+                variant.label_bytes,
+                variant.classes,
+            )
+            .unwrap(),
+        )
     }
+}
+
+/// Load the images in `layout`, arranging them while converting the bytes to `f32`.
+fn get_dataset_with_layout(
+    base_path: &str,
+    python_base_path: &str,
+    variant: &Variant,
+    layout: Layout,
+    normalized: bool,
+) -> Data<IxDyn> {
+    let ((trn_img, trn_lbl), (tst_img, tst_lbl)) =
+        get_raw_dataset(base_path, python_base_path, variant);
+    let scale = if normalized { 256. } else { 1. };
     Data {
+        trn_img: layout.map_nchw(&trn_img, |x| x as f32 / scale),
+        trn_lbl,
+        tst_img: layout.map_nchw(&tst_img, |x| x as f32 / scale),
+        tst_lbl,
+    }
+}
+
+fn get_dataset(
+    base_path: &str,
+    python_base_path: &str,
+    variant: &Variant,
+    normalized: bool,
+) -> Data {
+    let Data {
         trn_img,
         trn_lbl,
         tst_img,
         tst_lbl,
+    } = get_dataset_with_layout(
+        base_path,
+        python_base_path,
+        variant,
+        Layout::Nchw,
+        normalized,
+    );
+    Data {
+        trn_img: trn_img.into_dimensionality().unwrap(),
+        trn_lbl,
+        tst_img: tst_img.into_dimensionality().unwrap(),
+        tst_lbl,
     }
 }
 
 pub mod cifar10 {
     pub use super::Data;
+    use crate::layout::Layout;
+    use ndarray::IxDyn;
     static BASE_PATH: &str = "data/cifar-10-batches-bin/";
+    static PYTHON_BASE_PATH: &str = "data/cifar-10-batches-py/";
     pub fn new() -> Data {
-        super::get_dataset(BASE_PATH, false)
+        super::get_dataset(BASE_PATH, PYTHON_BASE_PATH, &super::CIFAR10, false)
     }
     pub fn new_normalized() -> Data {
-        super::get_dataset(BASE_PATH, true)
+        super::get_dataset(BASE_PATH, PYTHON_BASE_PATH, &super::CIFAR10, true)
+    }
+    /// Images in `layout` instead of NCHW, see `layout::Layout`.
+    pub fn new_with_layout(layout: Layout, normalized: bool) -> Data<IxDyn> {
+        super::get_dataset_with_layout(
+            BASE_PATH,
+            PYTHON_BASE_PATH,
+            &super::CIFAR10,
+            layout,
+            normalized,
+        )
     }
 
     #[cfg(feature = "download")]
@@ -108,12 +313,25 @@ pub mod cifar10 {
 }
 pub mod cifar100 {
     pub use super::Data;
+    use crate::layout::Layout;
+    use ndarray::IxDyn;
     static BASE_PATH: &str = "data/cifar-100-binary/";
+    static PYTHON_BASE_PATH: &str = "data/cifar-100-python/";
     pub fn new() -> Data {
-        super::get_dataset(BASE_PATH, false)
+        super::get_dataset(BASE_PATH, PYTHON_BASE_PATH, &super::CIFAR100, false)
     }
     pub fn new_normalized() -> Data {
-        super::get_dataset(BASE_PATH, true)
+        super::get_dataset(BASE_PATH, PYTHON_BASE_PATH, &super::CIFAR100, true)
+    }
+    /// Images in `layout` instead of NCHW, see `layout::Layout`.
+    pub fn new_with_layout(layout: Layout, normalized: bool) -> Data<IxDyn> {
+        super::get_dataset_with_layout(
+            BASE_PATH,
+            PYTHON_BASE_PATH,
+            &super::CIFAR100,
+            layout,
+            normalized,
+        )
     }
 
     #[cfg(feature = "download")]
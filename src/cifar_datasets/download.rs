@@ -1,7 +1,4 @@
-use tar::Archive;
-
 use std::path::{Path, PathBuf};
-use std::{fs, io};
 
 use crate::download_helper::downloader;
 
@@ -29,16 +26,5 @@ pub fn download_and_extract(base_path: &str, use_large_dataset: bool) -> Result<
 
 fn extract_gz(archive_name: &str, download_dir: &Path) -> Result<(), String> {
     let archive = download_dir.join(&archive_name);
-    let extract_to = download_dir.join(&archive_name.replace(".gz", ""));
-
-    println!("Extracting archive {:?} to {:?}...", archive, extract_to);
-    let file_in = fs::File::open(&archive)
-        .or_else(|e| Err(format!("Failed to open archive {:?}: {:?}", archive, e)))?;
-    let file_in = io::BufReader::new(file_in);
-    let gz = flate2::bufread::GzDecoder::new(file_in);
-    let mut archive = Archive::new(gz);
-    let dst: String = "data".to_string();
-    archive.unpack(dst).unwrap();
-
-    Ok(())
+    downloader::extract_tar_gz(&archive, Path::new("data"))
 }
@@ -0,0 +1,201 @@
+//! Color space conversions of image batches.
+//!
+//! Color images use the NCHW layout of `cifar_builder::Data` with the channels in RGB order,
+//! grayscale images either the NHW layout of `mnist_builder::Data` or NCHW with one channel,
+//! see `add_channel_axis`. The conversions don't depend on the value range, so raw [0, 255]
+//! and normalized images both work.
+//!
+//! ```no_run
+//! use datasets::color::{add_channel_axis, gray_to_rgb, rgb_to_gray};
+//! use datasets::{cifar10, mnist};
+//!
+//! // Train on grayscale CIFAR, test on MNIST digits replicated to three channels.
+//! let cifar = cifar10::new_normalized();
+//! let gray_cifar = rgb_to_gray(&cifar.trn_img);
+//! let mnist = mnist::new_normalized();
+//! let rgb_mnist = gray_to_rgb(&add_channel_axis(mnist.tst_img));
+//! ```
+
+use ndarray::prelude::*;
+use ndarray::{Data, Zip};
+
+/// Turn NHW images into NCHW images with a single channel. The data isn't copied.
+pub fn add_channel_axis(images: Array3<f32>) -> Array4<f32> {
+    images.insert_axis(Axis(1))
+}
+
+/// Drop the channel axis of single-channel NCHW images. The data isn't copied.
+pub fn remove_channel_axis(images: Array4<f32>) -> Array3<f32> {
+    assert_channels(&images, 1);
+    images.index_axis_move(Axis(1), 0)
+}
+
+fn assert_channels<S: Data<Elem = f32>>(images: &ArrayBase<S, Ix4>, channels: usize) {
+    assert!(
+        images.len_of(Axis(1)) == channels,
+        "Expected NCHW images with {} channels, got shape {:?}.",
+        channels,
+        images.shape()
+    );
+}
+
+/// Convert every pixel of RGB images with `f`.
+fn map_pixels<S, F>(images: &ArrayBase<S, Ix4>, f: F) -> Array4<f32>
+where
+    S: Data<Elem = f32>,
+    F: Fn(f32, f32, f32) -> (f32, f32, f32),
+{
+    assert_channels(images, 3);
+    let mut out = images.to_owned();
+    for mut image in out.outer_iter_mut() {
+        let (first, rest) = image.view_mut().split_at(Axis(0), 1);
+        let (second, third) = rest.split_at(Axis(0), 1);
+        Zip::from(first).and(second).and(third).apply(|a, b, c| {
+            let (x, y, z) = f(*a, *b, *c);
+            *a = x;
+            *b = y;
+            *c = z;
+        });
+    }
+    out
+}
+
+/// Luma of RGB images with the ITU-R 601 weights, as NCHW images with one channel.
+pub fn rgb_to_gray<S: Data<Elem = f32>>(images: &ArrayBase<S, Ix4>) -> Array4<f32> {
+    assert_channels(images, 3);
+    let channel = |c: usize| images.index_axis(Axis(1), c);
+    let gray = &channel(0) * 0.299 + &channel(1) * 0.587 + &channel(2) * 0.114;
+    add_channel_axis(gray)
+}
+
+/// Replicate the channel of single-channel NCHW images three times.
+pub fn gray_to_rgb<S: Data<Elem = f32>>(images: &ArrayBase<S, Ix4>) -> Array4<f32> {
+    assert_channels(images, 1);
+    let (n, _, rows, cols) = images.dim();
+    images.broadcast((n, 3, rows, cols)).unwrap().to_owned()
+}
+
+/// Convert RGB to HSV. Hue and saturation are in [0, 1], the value keeps the range of the
+/// input. Hue is 0 for gray pixels.
+pub fn rgb_to_hsv<S: Data<Elem = f32>>(images: &ArrayBase<S, Ix4>) -> Array4<f32> {
+    map_pixels(images, |r, g, b| {
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let saturation = if max > 0. { delta / max } else { 0. };
+        let hue = if delta == 0. {
+            0.
+        } else if max == r {
+            ((g - b) / delta).rem_euclid(6.)
+        } else if max == g {
+            (b - r) / delta + 2.
+        } else {
+            (r - g) / delta + 4.
+        };
+        (hue / 6., saturation, max)
+    })
+}
+
+/// Inverse of `rgb_to_hsv`.
+pub fn hsv_to_rgb<S: Data<Elem = f32>>(images: &ArrayBase<S, Ix4>) -> Array4<f32> {
+    map_pixels(images, |h, s, v| {
+        let h = (h * 6.).rem_euclid(6.);
+        let chroma = v * s;
+        let x = chroma * (1. - (h % 2. - 1.).abs());
+        let m = v - chroma;
+        let (r, g, b) = match h as usize {
+            0 => (chroma, x, 0.),
+            1 => (x, chroma, 0.),
+            2 => (0., chroma, x),
+            3 => (0., x, chroma),
+            4 => (x, 0., chroma),
+            _ => (chroma, 0., x),
+        };
+        (r + m, g + m, b + m)
+    })
+}
+
+/// Convert RGB to YUV with the analog ITU-R 601 matrix. Y keeps the range of the input, U
+/// and V are centered around zero.
+pub fn rgb_to_yuv<S: Data<Elem = f32>>(images: &ArrayBase<S, Ix4>) -> Array4<f32> {
+    map_pixels(images, |r, g, b| {
+        let y = 0.299 * r + 0.587 * g + 0.114 * b;
+        (y, 0.492 * (b - y), 0.877 * (r - y))
+    })
+}
+
+/// Inverse of `rgb_to_yuv`.
+pub fn yuv_to_rgb<S: Data<Elem = f32>>(images: &ArrayBase<S, Ix4>) -> Array4<f32> {
+    map_pixels(images, |y, u, v| {
+        let b = y + u / 0.492;
+        let r = y + v / 0.877;
+        let g = (y - 0.299 * r - 0.114 * b) / 0.587;
+        (r, g, b)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Random RGB images in [0, 1] including pure gray, black and white pixels.
+    fn rgb_images() -> Array4<f32> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut images = Array4::from_shape_simple_fn((4, 3, 5, 5), || rng.gen_range(0.0..1.0));
+        images.slice_mut(s![0, .., 0, 0]).fill(0.5);
+        images.slice_mut(s![0, .., 0, 1]).fill(0.);
+        images.slice_mut(s![0, .., 0, 2]).fill(1.);
+        images
+    }
+
+    fn assert_close(a: &Array4<f32>, b: &Array4<f32>) {
+        assert_eq!(a.dim(), b.dim());
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < 1e-5, "{} != {}", x, y);
+        }
+    }
+
+    #[test]
+    fn hsv_round_trip() {
+        let images = rgb_images();
+        let hsv = rgb_to_hsv(&images);
+        assert!(hsv.iter().all(|&x| (0. ..=1.).contains(&x)));
+        // Gray pixels have neither hue nor saturation.
+        assert_eq!(hsv.slice(s![0, .., 0, 0]), arr1(&[0., 0., 0.5]));
+        assert_close(&hsv_to_rgb(&hsv), &images);
+        let pure = arr1(&[1., 0., 0.]).into_shape((1, 3, 1, 1)).unwrap();
+        assert_eq!(rgb_to_hsv(&pure).into_raw_vec(), vec![0., 1., 1.]);
+    }
+
+    #[test]
+    fn yuv_round_trip() {
+        let images = rgb_images();
+        let yuv = rgb_to_yuv(&images);
+        assert_close(
+            &yuv.slice(s![.., ..1, .., ..]).to_owned(),
+            &rgb_to_gray(&images),
+        );
+        // Gray pixels have no chroma.
+        assert!(yuv.slice(s![0, 1.., 0, ..3]).iter().all(|x| x.abs() < 1e-6));
+        assert_close(&yuv_to_rgb(&yuv), &images);
+    }
+
+    #[test]
+    fn gray_round_trip() {
+        let gray = rgb_images().slice(s![.., ..1, .., ..]).to_owned();
+        let rgb = gray_to_rgb(&gray);
+        assert_eq!(rgb.dim(), (4, 3, 5, 5));
+        assert_eq!(rgb.index_axis(Axis(1), 2), gray.index_axis(Axis(1), 0));
+        assert_close(&rgb_to_gray(&rgb), &gray);
+        let nhw = remove_channel_axis(gray.clone());
+        assert_eq!(add_channel_axis(nhw), gray);
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected NCHW images with 3 channels")]
+    fn rejects_wrong_channel_count() {
+        rgb_to_hsv(&Array4::<f32>::zeros((1, 1, 2, 2)));
+    }
+}
@@ -0,0 +1,204 @@
+//! Image datasets distributed as CSV files with one image per line, as done on Kaggle for the
+//! MNIST digit recognizer (`train.csv`) and Sign Language MNIST.
+//!
+//! ```no_run
+//! use datasets::csv_images::CsvImages;
+//!
+//! let trn = CsvImages::new("data/kaggle-mnist/train.csv").load().unwrap();
+//! assert_eq!(trn.img.shape()[1..], [28, 28]);
+//! ```
+
+use ndarray::{Array2, Array3};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::image_folder;
+use crate::mnist_datasets::mnist_builder;
+
+/// Images in the NHW layout of `mnist_builder::Data` with one-hot labels.
+pub struct Data {
+    pub img: Array3<f32>,
+    pub lbl: Array2<f32>,
+}
+
+/// Configures how a CSV file of flattened images is read.
+///
+/// By default the file starts with a header line, the first column holds the label and the
+/// remaining 784 columns the pixels of a 28 x 28 image, row by row.
+pub struct CsvImages {
+    path: PathBuf,
+    label_column: Option<usize>,
+    header: bool,
+    size: (usize, usize),
+    classes: Option<usize>,
+    normalized: bool,
+}
+
+impl CsvImages {
+    pub fn new<P: AsRef<Path>>(path: P) -> CsvImages {
+        CsvImages {
+            path: path.as_ref().to_path_buf(),
+            label_column: Some(0),
+            header: true,
+            size: (28, 28),
+            classes: None,
+            normalized: false,
+        }
+    }
+
+    /// Column holding the label, all other columns are pixels. `None` for unlabeled files like
+    /// the Kaggle `test.csv`, which results in labels with zero classes.
+    pub fn label_column(mut self, label_column: Option<usize>) -> CsvImages {
+        self.label_column = label_column;
+        self
+    }
+
+    /// Whether the first line holds column names and has to be skipped.
+    pub fn header(mut self, header: bool) -> CsvImages {
+        self.header = header;
+        self
+    }
+
+    /// Shape of a single image, `rows * cols` has to match the number of pixel columns.
+    pub fn shape(mut self, rows: usize, cols: usize) -> CsvImages {
+        self.size = (rows, cols);
+        self
+    }
+
+    /// Number of classes of the one-hot labels, by default the highest label plus one.
+    pub fn classes(mut self, classes: usize) -> CsvImages {
+        self.classes = Some(classes);
+        self
+    }
+
+    /// Scale pixel values the same way as `new_normalized` of the other datasets.
+    pub fn normalized(mut self, normalized: bool) -> CsvImages {
+        self.normalized = normalized;
+        self
+    }
+
+    pub fn load(&self) -> Result<Data, String> {
+        let (img, labels) = self.read(&self.path)?;
+        let classes = match (self.classes, self.label_column) {
+            (_, None) => 0,
+            (Some(classes), _) => classes,
+            (None, _) => labels.iter().max().map_or(0, |&l| l + 1),
+        };
+        let lbl = self.one_hot(&labels, classes)?;
+        Ok(Data { img, lbl })
+    }
+
+    /// Read a training and a test file with the same settings into `mnist_builder::Data`.
+    ///
+    /// Unless set with `classes`, the number of classes is derived from both files, so a class
+    /// missing from one of them doesn't change the label width.
+    pub fn load_train_test<P: AsRef<Path>>(
+        &self,
+        tst_path: P,
+    ) -> Result<mnist_builder::Data, String> {
+        if self.label_column.is_none() {
+            return Err("Training and test files need a label column.".to_string());
+        }
+        let (trn_img, trn_labels) = self.read(&self.path)?;
+        let (tst_img, tst_labels) = self.read(tst_path.as_ref())?;
+        let classes = self.classes.unwrap_or_else(|| {
+            trn_labels
+                .iter()
+                .chain(&tst_labels)
+                .max()
+                .map_or(0, |&l| l + 1)
+        });
+        Ok(mnist_builder::Data {
+            trn_img,
+            trn_lbl: self.one_hot(&trn_labels, classes)?,
+            tst_img,
+            tst_lbl: self.one_hot(&tst_labels, classes)?,
+        })
+    }
+
+    fn read(&self, path: &Path) -> Result<(Array3<f32>, Vec<usize>), String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read file {:?}: {:?}", path, e))?;
+        let (rows, cols) = self.size;
+        let columns = rows * cols + self.label_column.map_or(0, |_| 1);
+        let mut pixels: Vec<f32> = Vec::new();
+        let mut labels: Vec<usize> = Vec::new();
+        let lines = content.lines().enumerate().skip(self.header as usize);
+        for (number, line) in lines.filter(|(_, line)| !line.trim().is_empty()) {
+            let err = |what: String| format!("{} in line {} of {:?}.", what, number + 1, path);
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            if fields.len() != columns {
+                return Err(err(format!(
+                    "Expected {} columns for {} x {} images, got {}",
+                    columns,
+                    rows,
+                    cols,
+                    fields.len()
+                )));
+            }
+            for (i, field) in fields.iter().enumerate() {
+                if Some(i) == self.label_column {
+                    let label = field
+                        .parse()
+                        .map_err(|_| err(format!("Invalid label {:?}", field)))?;
+                    labels.push(label);
+                } else {
+                    let pixel = field
+                        .parse()
+                        .map_err(|_| err(format!("Invalid pixel {:?}", field)))?;
+                    pixels.push(pixel);
+                }
+            }
+        }
+        let len = pixels.len() / (rows * cols).max(1);
+        let mut img = Array3::from_shape_vec((len, rows, cols), pixels)
+            .map_err(|e| format!("Failed to read images of {:?}: {:?}", path, e))?;
+        if self.normalized {
+            img.mapv_inplace(|x| x / 256.);
+        }
+        if self.label_column.is_none() {
+            labels = vec![0; len];
+        }
+        Ok((img, labels))
+    }
+
+    fn one_hot(&self, labels: &[usize], classes: usize) -> Result<Array2<f32>, String> {
+        if self.label_column.is_none() {
+            return Ok(Array2::zeros((labels.len(), 0)));
+        }
+        if let Some(&label) = labels.iter().find(|&&l| l >= classes) {
+            return Err(format!(
+                "Label {} of {:?} exceeds the {} classes.",
+                label, self.path, classes
+            ));
+        }
+        Ok(image_folder::one_hot(labels, classes))
+    }
+}
+
+/// Sign Language MNIST: 28 x 28 grayscale hand signs of the letters A to Y. The letters J and
+/// Z require motion and are missing, so labels 9 and 25 never occur.
+pub mod sign_language_mnist {
+    use super::CsvImages;
+    pub use crate::mnist_datasets::mnist_builder::Data;
+
+    static BASE_PATH: &str = "data/sign-language-mnist/";
+    static TRN_FILENAME: &str = "sign_mnist_train.csv";
+    static TST_FILENAME: &str = "sign_mnist_test.csv";
+    static CLASSES: usize = 25;
+
+    fn get_data(normalized: bool) -> Data {
+        CsvImages::new(format!("{}{}", BASE_PATH, TRN_FILENAME))
+            .classes(CLASSES)
+            .normalized(normalized)
+            .load_train_test(format!("{}{}", BASE_PATH, TST_FILENAME))
+            .unwrap()
+    }
+    pub fn new() -> Data {
+        get_data(false)
+    }
+    pub fn new_normalized() -> Data {
+        get_data(true)
+    }
+}
@@ -0,0 +1,226 @@
+//! Operations shared by the image datasets with one-hot labels, `mnist_builder::Data` (also
+//! used by USPS, QuickDraw and the CSV images) and `cifar_builder::Data` (also used by
+//! Downsampled ImageNet).
+//!
+//! The methods of `Dataset` copy the selected samples into a new dataset of the same type, the
+//! trait has to be in scope to call them:
+//!
+//! ```no_run
+//! use datasets::dataset::Dataset;
+//! use datasets::mnist;
+//!
+//! let data = mnist::new_normalized();
+//! let digits = data.select_classes(&[3, 8], true);
+//! digits.save_npz("mnist_3_8.npz").unwrap();
+//! ```
+
+use ndarray::prelude::*;
+use ndarray::RemoveAxis;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use crate::long_tail::{self, Imbalance};
+use crate::npy::{NpzReader, NpzWriter};
+use crate::{split, subset};
+
+/// Training images and labels followed by test images and labels, as stored by `save_npz`.
+pub(crate) type Parts<D> = (Array<f32, D>, Array2<f32>, Array<f32, D>, Array2<f32>);
+
+fn read_array<D: Dimension>(
+    npz: &mut NpzReader<impl Read + Seek>,
+    name: &str,
+) -> Result<Array<f32, D>, String> {
+    npz.by_name::<f32>(name)?
+        .into_dimensionality::<D>()
+        .map_err(|e| format!("Unexpected shape of {}: {:?}", name, e))
+}
+
+/// Read the arrays stored by `Dataset::save_npz`, with images of dimension `D`.
+pub(crate) fn read_npz<D: Dimension, P: AsRef<Path>>(path: P) -> Result<Parts<D>, String> {
+    let mut npz = NpzReader::open(path)?;
+    Ok((
+        read_array(&mut npz, "trn_img")?,
+        read_array(&mut npz, "trn_lbl")?,
+        read_array(&mut npz, "tst_img")?,
+        read_array(&mut npz, "tst_lbl")?,
+    ))
+}
+
+/// Images with one-hot labels, split into a training and a test set.
+pub trait Dataset: Sized {
+    /// Dimension of the image arrays, whose first axis indexes samples.
+    type Dim: Dimension + RemoveAxis;
+
+    fn from_parts(
+        trn_img: Array<f32, Self::Dim>,
+        trn_lbl: Array2<f32>,
+        tst_img: Array<f32, Self::Dim>,
+        tst_lbl: Array2<f32>,
+    ) -> Self;
+
+    /// Training images and labels.
+    fn trn(&self) -> (&Array<f32, Self::Dim>, &Array2<f32>);
+
+    /// Test images and labels.
+    fn tst(&self) -> (&Array<f32, Self::Dim>, &Array2<f32>);
+
+    /// Store the images and labels as `trn_img`, `trn_lbl`, `tst_img` and `tst_lbl` in a
+    /// `.npz` archive, which `numpy.load` can read.
+    fn save_npz<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let ((trn_img, trn_lbl), (tst_img, tst_lbl)) = (self.trn(), self.tst());
+        let mut npz = NpzWriter::create(path, false)?;
+        npz.add_array("trn_img", trn_img)?;
+        npz.add_array("trn_lbl", trn_lbl)?;
+        npz.add_array("tst_img", tst_img)?;
+        npz.add_array("tst_lbl", tst_lbl)?;
+        npz.finish()?;
+        Ok(())
+    }
+
+    /// Split the training set into the training samples at `trn` and the validation samples
+    /// at `val`, which take the place of the test set. See `split::stratified_split` and
+    /// `split::StratifiedKFold` for computing the indices.
+    fn train_val(&self, trn: &[usize], val: &[usize]) -> Self {
+        let (images, labels) = self.trn();
+        let (trn_img, trn_lbl) = split::select(images, labels, trn);
+        let (tst_img, tst_lbl) = split::select(images, labels, val);
+        Self::from_parts(trn_img, trn_lbl, tst_img, tst_lbl)
+    }
+
+    /// Keep the samples of `trn` in the training set and of `tst` in the test set.
+    fn select(&self, trn: &[usize], tst: &[usize]) -> Self {
+        let ((trn_img, trn_lbl), (tst_img, tst_lbl)) = (self.trn(), self.tst());
+        let (trn_img, trn_lbl) = split::select(trn_img, trn_lbl, trn);
+        let (tst_img, tst_lbl) = split::select(tst_img, tst_lbl, tst);
+        Self::from_parts(trn_img, trn_lbl, tst_img, tst_lbl)
+    }
+
+    /// Keep the samples of `classes` in both sets. With `remap` the labels only have a column
+    /// for every selected class, `classes[i]` becomes class `i`.
+    fn select_classes(&self, classes: &[usize], remap: bool) -> Self {
+        let ((trn_img, trn_lbl), (tst_img, tst_lbl)) = (self.trn(), self.tst());
+        let (trn_img, mut trn_lbl) =
+            split::select(trn_img, trn_lbl, &subset::class_indices(trn_lbl, classes));
+        let (tst_img, mut tst_lbl) =
+            split::select(tst_img, tst_lbl, &subset::class_indices(tst_lbl, classes));
+        if remap {
+            trn_lbl = subset::remap_classes(&trn_lbl, classes);
+            tst_lbl = subset::remap_classes(&tst_lbl, classes);
+        }
+        Self::from_parts(trn_img, trn_lbl, tst_img, tst_lbl)
+    }
+
+    /// Keep the samples of both sets for which `predicate(image, label)` holds.
+    fn filter<F>(&self, predicate: F) -> Self
+    where
+        F: Fn(ArrayView<f32, <Self::Dim as Dimension>::Smaller>, ArrayView1<f32>) -> bool,
+    {
+        let ((trn_img, trn_lbl), (tst_img, tst_lbl)) = (self.trn(), self.tst());
+        self.select(
+            &subset::filter_indices(trn_img, trn_lbl, &predicate),
+            &subset::filter_indices(tst_img, tst_lbl, &predicate),
+        )
+    }
+
+    /// Keep at most `cap` random training samples per class, the test set is unchanged.
+    fn cap_per_class(&self, cap: usize, seed: u64) -> Self {
+        self.cap_classes(&vec![cap; self.trn().1.ncols()], seed)
+    }
+
+    /// Keep at most `caps[c]` random training samples of every class `c`, the test set is
+    /// unchanged.
+    fn cap_classes(&self, caps: &[usize], seed: u64) -> Self {
+        let tst: Vec<usize> = (0..self.tst().1.nrows()).collect();
+        self.select(&subset::capped_indices(self.trn().1, caps, seed), &tst)
+    }
+
+    /// Long-tailed training set with the largest and the smallest class differing by
+    /// `ratio`, see `long_tail`. Class 0 keeps as many samples as the smallest training class
+    /// has. Returns the dataset and the number of training samples of every class.
+    fn long_tailed(&self, imbalance: Imbalance, ratio: f64, seed: u64) -> (Self, Vec<usize>) {
+        let classes = self.trn().1.ncols();
+        let mut available = subset::class_counts(self.trn().1);
+        available.resize(classes, 0);
+        let n_max = available.iter().copied().min().unwrap_or(0);
+        let caps = long_tail::counts(n_max, classes, imbalance, ratio);
+        let data = self.cap_classes(&caps, seed);
+        (data, caps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cifar_datasets::cifar_builder::Data;
+    use crate::split::Labels;
+
+    /// 40 training and 10 test images of 5 classes, image `i` is filled with `i`.
+    fn data() -> Data {
+        let labels = |n: usize| Array2::from_shape_fn((n, 5), |(i, c)| (i % 5 == c) as u8 as f32);
+        Data {
+            trn_img: Array4::from_shape_fn((40, 1, 2, 2), |(i, _, _, _)| i as f32),
+            trn_lbl: labels(40),
+            tst_img: Array4::from_shape_fn((10, 1, 2, 2), |(i, _, _, _)| 100. + i as f32),
+            tst_lbl: labels(10),
+        }
+    }
+
+    /// Index of every training image, recovered from its pixels.
+    fn trn_ids(data: &Data) -> Vec<usize> {
+        data.trn_img
+            .outer_iter()
+            .map(|image| image[[0, 0, 0]] as usize)
+            .collect()
+    }
+
+    #[test]
+    fn train_val_and_select_classes() {
+        let data = data();
+        let split = data.train_val(&[0, 2, 4], &[1, 3]);
+        assert_eq!(trn_ids(&split), vec![0, 2, 4]);
+        assert_eq!(split.tst_img[[1, 0, 0, 0]], 3.);
+        assert_eq!(split.tst_lbl.classes(), vec![1, 3]);
+
+        let binary = data.select_classes(&[4, 1], true);
+        assert_eq!(binary.trn_lbl.ncols(), 2);
+        assert_eq!(binary.trn_img.shape()[0], 16);
+        assert_eq!(binary.tst_lbl.classes(), vec![1, 0, 1, 0]);
+        assert_eq!(data.select_classes(&[4, 1], false).trn_lbl.ncols(), 5);
+    }
+
+    #[test]
+    fn filter_and_caps() {
+        let data = data();
+        let even = data.filter(|image, _| image[[0, 0, 0]] as usize % 2 == 0);
+        assert_eq!(even.trn_img.shape()[0], 20);
+        assert_eq!(even.tst_img.shape()[0], 5);
+
+        let capped = data.cap_per_class(3, 0);
+        assert_eq!(subset::class_counts(&capped.trn_lbl), vec![3; 5]);
+        assert_eq!(capped.tst_lbl, data.tst_lbl);
+        assert_eq!(trn_ids(&capped), trn_ids(&data.cap_per_class(3, 0)));
+
+        let (imbalanced, counts) = data.long_tailed(Imbalance::Exponential, 8., 0);
+        assert_eq!(counts, vec![8, 4, 2, 1, 1]);
+        assert_eq!(subset::class_counts(&imbalanced.trn_lbl), counts);
+
+        // The smallest class bounds the largest one of the long tail.
+        let uneven = data.select(&(3..40).collect::<Vec<_>>(), &[]);
+        let (imbalanced, counts) = uneven.long_tailed(Imbalance::Step, 2., 0);
+        assert_eq!(counts, vec![7, 7, 3, 3, 3]);
+        assert_eq!(subset::class_counts(&imbalanced.trn_lbl), counts);
+    }
+
+    #[test]
+    fn npz_round_trip() {
+        let path = std::env::temp_dir().join(format!("dataset-{}.npz", std::process::id()));
+        let data = data().select_classes(&[0, 3], true);
+        data.save_npz(&path).unwrap();
+        let loaded = Data::load_npz(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.trn_img, data.trn_img);
+        assert_eq!(loaded.trn_lbl, data.trn_lbl);
+        assert_eq!(loaded.tst_img, data.tst_img);
+        assert_eq!(loaded.tst_lbl, data.tst_lbl);
+    }
+}
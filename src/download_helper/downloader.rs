@@ -1,10 +1,34 @@
+extern crate bzip2;
 extern crate flate2;
 extern crate reqwest;
+extern crate tar;
+extern crate zip;
 
 use std::path::{Path, PathBuf};
 use std::{fs, io};
 
 pub fn download(base_path: &str, base_url: String, online_files: Vec<&str>) -> Result<(), String> {
+    let download_dir = create_download_dir(base_path)?;
+
+    //parallelize?
+    for file in online_files.iter() {
+        let url = format!("{}/{}", base_url, file);
+        let res = single_download(&download_dir, url, file);
+        match res {
+            Ok(()) => continue,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    Ok(())
+}
+
+/// Download `url` to `file_name` in `base_path`, for urls which don't end in a usable file name.
+pub fn download_file(base_path: &str, url: String, file_name: &str) -> Result<(), String> {
+    let download_dir = create_download_dir(base_path)?;
+    single_download(&download_dir, url, file_name)
+}
+
+fn create_download_dir(base_path: &str) -> Result<PathBuf, String> {
     let download_dir = PathBuf::from(base_path);
     if !download_dir.exists() {
         println!(
@@ -18,21 +42,10 @@ pub fn download(base_path: &str, base_url: String, online_files: Vec<&str>) -> R
             ))
         })?;
     }
-
-    //parallelize?
-    for file in online_files.iter() {
-        let res = single_download(&download_dir, base_url.clone(), file);
-        match res {
-            Ok(()) => continue,
-            Err(e) => eprintln!("{}", e),
-        }
-    }
-    Ok(())
+    Ok(download_dir)
 }
 
-fn single_download(download_dir: &Path, base_url: String, archive: &str) -> Result<(), String> {
-    let url = format!("{}/{}", base_url, archive);
-
+fn single_download(download_dir: &Path, url: String, archive: &str) -> Result<(), String> {
     let file_name = download_dir.join(&archive);
 
     if file_name.exists() {
@@ -58,3 +71,67 @@ fn single_download(download_dir: &Path, base_url: String, archive: &str) -> Resu
     }
     Ok(())
 }
+
+pub fn extract_tar_gz(archive: &Path, dst: &Path) -> Result<(), String> {
+    println!("Extracting archive {:?} to {:?}...", archive, dst);
+    let file_in = fs::File::open(archive)
+        .map_err(|e| format!("Failed to open archive {:?}: {:?}", archive, e))?;
+    let file_in = io::BufReader::new(file_in);
+    let gz = flate2::bufread::GzDecoder::new(file_in);
+    let mut archive_reader = tar::Archive::new(gz);
+    archive_reader
+        .unpack(dst)
+        .map_err(|e| format!("Failed to extract archive {:?}: {:?}", archive, e))
+}
+
+pub fn extract_zip(archive: &Path, dst: &Path) -> Result<(), String> {
+    println!("Extracting archive {:?} to {:?}...", archive, dst);
+    let file_in = fs::File::open(archive)
+        .map_err(|e| format!("Failed to open archive {:?}: {:?}", archive, e))?;
+    let mut zip = zip::ZipArchive::new(io::BufReader::new(file_in))
+        .map_err(|e| format!("Failed to read archive {:?}: {:?}", archive, e))?;
+    for i in 0..zip.len() {
+        let mut entry = zip
+            .by_index(i)
+            .map_err(|e| format!("Failed to read archive {:?}: {:?}", archive, e))?;
+        let extract_to = match entry.enclosed_name() {
+            Some(name) => dst.join(name),
+            None => continue,
+        };
+        if entry.is_dir() {
+            fs::create_dir_all(&extract_to)
+                .map_err(|e| format!("Failed to create directory {:?}: {:?}", extract_to, e))?;
+            continue;
+        }
+        if let Some(parent) = extract_to.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {:?}: {:?}", parent, e))?;
+        }
+        let file_out = fs::File::create(&extract_to)
+            .map_err(|e| format!("Failed to create extracted file {:?}: {:?}", extract_to, e))?;
+        io::copy(&mut entry, &mut io::BufWriter::new(file_out))
+            .map_err(|e| format!("Failed to extract {:?}: {:?}", extract_to, e))?;
+    }
+    Ok(())
+}
+
+/// Decompress a single `.bz2` file next to it, dropping the extension.
+pub fn extract_bz2(archive: &Path) -> Result<PathBuf, String> {
+    let extract_to = archive.with_extension("");
+    if extract_to.exists() {
+        println!(
+            "  Extracted file {:?} already exists, skipping extraction.",
+            extract_to
+        );
+        return Ok(extract_to);
+    }
+    println!("Extracting archive {:?} to {:?}...", archive, extract_to);
+    let file_in = fs::File::open(archive)
+        .map_err(|e| format!("Failed to open archive {:?}: {:?}", archive, e))?;
+    let mut bz = bzip2::read::BzDecoder::new(io::BufReader::new(file_in));
+    let file_out = fs::File::create(&extract_to)
+        .map_err(|e| format!("Failed to create extracted file {:?}: {:?}", extract_to, e))?;
+    io::copy(&mut bz, &mut io::BufWriter::new(file_out))
+        .map_err(|e| format!("Failed to extract archive {:?}: {:?}", archive, e))?;
+    Ok(extract_to)
+}
@@ -0,0 +1,174 @@
+//! N-way K-shot episode sampling for meta-learning.
+//!
+//! The sampler works on class indices, so it can be built from `omniglot::Split::character`
+//! as well as from the one-hot labels of `mnist::Data` or `cifar100::Data`.
+
+use ndarray::prelude::*;
+use ndarray::{Data, RemoveAxis};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::split::Labels;
+
+/// A sampled few-shot task.
+///
+/// `support`/`query` hold sample indices into the dataset, `support_lbl`/`query_lbl` the
+/// episode labels `0..n_way`. Episode label `j` belongs to dataset class `classes[j]`, shown
+/// rotated by `rotations[j]` quarter turns counter-clockwise.
+#[derive(Clone, Debug)]
+pub struct Episode {
+    pub support: Vec<usize>,
+    pub support_lbl: Vec<usize>,
+    pub query: Vec<usize>,
+    pub query_lbl: Vec<usize>,
+    pub classes: Vec<usize>,
+    pub rotations: Vec<usize>,
+}
+
+impl Episode {
+    /// Gather (and rotate) the support images from `images`, whose first axis indexes samples
+    /// and whose last two axes are rows and columns.
+    pub fn support_images<S, D>(&self, images: &ArrayBase<S, D>) -> Array<f32, D>
+    where
+        S: Data<Elem = f32>,
+        D: Dimension + RemoveAxis,
+    {
+        self.gather(images, &self.support, &self.support_lbl)
+    }
+
+    /// Gather (and rotate) the query images, see `support_images`.
+    pub fn query_images<S, D>(&self, images: &ArrayBase<S, D>) -> Array<f32, D>
+    where
+        S: Data<Elem = f32>,
+        D: Dimension + RemoveAxis,
+    {
+        self.gather(images, &self.query, &self.query_lbl)
+    }
+
+    fn gather<S, D>(
+        &self,
+        images: &ArrayBase<S, D>,
+        indices: &[usize],
+        lbl: &[usize],
+    ) -> Array<f32, D>
+    where
+        S: Data<Elem = f32>,
+        D: Dimension + RemoveAxis,
+    {
+        let mut out = images.select(Axis(0), indices);
+        for (i, &j) in lbl.iter().enumerate() {
+            if self.rotations[j] != 0 {
+                let rotated = rot90(images.index_axis(Axis(0), indices[i]), self.rotations[j]);
+                out.index_axis_mut(Axis(0), i).assign(&rotated);
+            }
+        }
+        out
+    }
+}
+
+/// View of `image` rotated by `k` quarter turns counter-clockwise in its last two axes.
+fn rot90<D: Dimension>(image: ArrayView<f32, D>, k: usize) -> ArrayView<f32, D> {
+    let (rows, cols) = (image.ndim() - 2, image.ndim() - 1);
+    let mut image = image;
+    for _ in 0..k % 4 {
+        image.swap_axes(rows, cols);
+        image.invert_axis(Axis(rows));
+    }
+    image
+}
+
+/// Samples N-way K-shot episodes from class-labelled data.
+pub struct EpisodeSampler {
+    by_class: Vec<Vec<usize>>,
+    rotations: bool,
+    rng: StdRng,
+}
+
+impl EpisodeSampler {
+    pub fn new(labels: &[usize], seed: u64) -> EpisodeSampler {
+        let classes = labels.iter().max().map_or(0, |&c| c + 1);
+        let mut by_class = vec![Vec::new(); classes];
+        for (i, &label) in labels.iter().enumerate() {
+            by_class[label].push(i);
+        }
+        EpisodeSampler {
+            by_class,
+            rotations: false,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Build a sampler from one-hot labels, e.g. `mnist::Data::trn_lbl`.
+    pub fn from_one_hot<S>(lbl: &ArrayBase<S, Ix2>, seed: u64) -> EpisodeSampler
+    where
+        S: Data<Elem = f32>,
+    {
+        EpisodeSampler::new(&lbl.classes(), seed)
+    }
+
+    /// Treat every rotation by 90, 180 and 270 degrees of a class as an additional class, as
+    /// commonly done for Omniglot. Requires square images.
+    pub fn with_rotations(mut self, rotations: bool) -> EpisodeSampler {
+        self.rotations = rotations;
+        self
+    }
+
+    /// Number of classes episodes are drawn from, including rotated ones.
+    pub fn num_classes(&self) -> usize {
+        let classes = self.by_class.iter().filter(|c| !c.is_empty()).count();
+        if self.rotations {
+            4 * classes
+        } else {
+            classes
+        }
+    }
+
+    /// Draw `n_way` distinct classes with `k_shot` support and `n_query` query samples each.
+    pub fn sample(&mut self, n_way: usize, k_shot: usize, n_query: usize) -> Episode {
+        let per_class = k_shot + n_query;
+        let rotations = if self.rotations { 4 } else { 1 };
+        let candidates: Vec<(usize, usize)> = self
+            .by_class
+            .iter()
+            .enumerate()
+            .filter(|(_, samples)| samples.len() >= per_class)
+            .flat_map(|(class, _)| (0..rotations).map(move |rotation| (class, rotation)))
+            .collect();
+        assert!(
+            candidates.len() >= n_way,
+            "Only {} classes have at least {} samples, cannot sample a {}-way episode.",
+            candidates.len(),
+            per_class,
+            n_way
+        );
+
+        let mut episode = Episode {
+            support: Vec::with_capacity(n_way * k_shot),
+            support_lbl: Vec::with_capacity(n_way * k_shot),
+            query: Vec::with_capacity(n_way * n_query),
+            query_lbl: Vec::with_capacity(n_way * n_query),
+            classes: Vec::with_capacity(n_way),
+            rotations: Vec::with_capacity(n_way),
+        };
+        let chosen: Vec<(usize, usize)> = candidates
+            .choose_multiple(&mut self.rng, n_way)
+            .cloned()
+            .collect();
+        for (j, (class, rotation)) in chosen.into_iter().enumerate() {
+            let samples: Vec<usize> = self.by_class[class]
+                .choose_multiple(&mut self.rng, per_class)
+                .cloned()
+                .collect();
+            episode.support.extend(&samples[..k_shot]);
+            episode
+                .support_lbl
+                .extend(std::iter::repeat(j).take(k_shot));
+            episode.query.extend(&samples[k_shot..]);
+            episode.query_lbl.extend(std::iter::repeat(j).take(n_query));
+            episode.classes.push(class);
+            episode.rotations.push(rotation);
+        }
+        episode
+    }
+}
@@ -0,0 +1,38 @@
+use std::path::Path;
+
+use crate::download_helper::downloader;
+
+const PLANETOID_BASE_URL: &str = "https://github.com/kimiyoung/planetoid/raw/master/data";
+const PLANETOID_SUFFIXES: &[&str] = &["x", "y", "tx", "ty", "allx", "ally", "graph", "test.index"];
+const CORA_BASE_URL: &str = "https://linqs-data.soe.ucsc.edu/public/lbc";
+const CORA_ARCHIVE: &str = "cora.tgz";
+
+pub fn download_planetoid(base_path: &str, name: &str) -> Result<(), String> {
+    println!("Attempting to download Planetoid {}...", name);
+    let files: Vec<String> = PLANETOID_SUFFIXES
+        .iter()
+        .map(|suffix| format!("ind.{}.{}", name, suffix))
+        .collect();
+    downloader::download(
+        base_path,
+        PLANETOID_BASE_URL.to_string(),
+        files.iter().map(|f| f.as_str()).collect(),
+    )
+}
+
+/// The archive contains a `cora` folder, so it is extracted into the parent of `base_path`.
+pub fn download_and_extract_cora(base_path: &str) -> Result<(), String> {
+    println!("Attempting to download and extract {}...", CORA_ARCHIVE);
+    downloader::download(base_path, CORA_BASE_URL.to_string(), vec![CORA_ARCHIVE])?;
+    let base_dir = Path::new(base_path);
+    if base_dir.join("cora.content").exists() {
+        println!(
+            "  Dataset already extracted to {:?}, skipping extraction.",
+            base_dir
+        );
+        return Ok(());
+    }
+    downloader::extract_tar_gz(&base_dir.join(CORA_ARCHIVE), Path::new("data"))?;
+    println!("done unpacking .tgz");
+    Ok(())
+}
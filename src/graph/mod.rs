@@ -0,0 +1,589 @@
+//! Citation graph benchmarks for node classification: Cora, CiteSeer and PubMed in the
+//! Planetoid split of Yang et al., and the raw Cora files of the LINQS group.
+//!
+//! Papers are nodes with bag of words features, citations are undirected edges.
+
+use ndarray::prelude::*;
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+
+use crate::pickle::{self, Value};
+use crate::split::Labels;
+
+#[cfg(feature = "download")]
+mod download;
+
+/// A graph with node features, node labels and a train/validation/test split of its nodes.
+///
+/// `edges` holds every undirected edge in both directions, sorted and without duplicates.
+/// The same adjacency in CSR layout is given by `indptr` and `indices`: the neighbours of node
+/// `i` are `indices[indptr[i]..indptr[i + 1]]`.
+#[derive(Clone, Debug)]
+pub struct Graph {
+    pub features: Array2<f32>,
+    pub labels: Array1<usize>,
+    pub edges: Vec<(usize, usize)>,
+    pub indptr: Vec<usize>,
+    pub indices: Vec<usize>,
+    pub train_mask: Array1<bool>,
+    pub val_mask: Array1<bool>,
+    pub test_mask: Array1<bool>,
+    pub class_names: Vec<String>,
+}
+
+impl Graph {
+    pub fn num_nodes(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn num_classes(&self) -> usize {
+        self.labels.iter().max().map_or(0, |&l| l + 1)
+    }
+
+    /// Neighbours of `node`.
+    pub fn neighbors(&self, node: usize) -> &[usize] {
+        &self.indices[self.indptr[node]..self.indptr[node + 1]]
+    }
+}
+
+fn build_graph(
+    features: Array2<f32>,
+    labels: Array1<usize>,
+    edges: Vec<(usize, usize)>,
+    masks: [&[usize]; 3],
+    class_names: Vec<String>,
+) -> Result<Graph, String> {
+    let nodes = labels.len();
+    if let Some(&(a, b)) = edges.iter().find(|&&(a, b)| a.max(b) >= nodes) {
+        return Err(format!(
+            "Edge ({}, {}) of a graph with {} nodes",
+            a, b, nodes
+        ));
+    }
+    if let Some(&i) = masks.iter().flat_map(|m| m.iter()).find(|&&i| i >= nodes) {
+        return Err(format!("Split contains node {} of {}", i, nodes));
+    }
+    let mut edges: Vec<(usize, usize)> = edges
+        .into_iter()
+        .flat_map(|(a, b)| vec![(a, b), (b, a)])
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+    let mut indptr = vec![0; nodes + 1];
+    for &(a, _) in &edges {
+        indptr[a + 1] += 1;
+    }
+    for i in 0..nodes {
+        indptr[i + 1] += indptr[i];
+    }
+    let indices = edges.iter().map(|&(_, b)| b).collect();
+    let mask = |nodes_in_split: &[usize]| {
+        let mut mask = Array1::from_elem(nodes, false);
+        for &i in nodes_in_split {
+            mask[i] = true;
+        }
+        mask
+    };
+    Ok(Graph {
+        train_mask: mask(masks[0]),
+        val_mask: mask(masks[1]),
+        test_mask: mask(masks[2]),
+        features,
+        labels,
+        edges,
+        indptr,
+        indices,
+        class_names,
+    })
+}
+
+/// The three Planetoid citation graphs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Planetoid {
+    Cora,
+    CiteSeer,
+    PubMed,
+}
+
+impl Planetoid {
+    fn name(self) -> &'static str {
+        match self {
+            Planetoid::Cora => "cora",
+            Planetoid::CiteSeer => "citeseer",
+            Planetoid::PubMed => "pubmed",
+        }
+    }
+}
+
+/// Dense matrix of a pickled `scipy.sparse.csr_matrix` or a numpy array.
+fn matrix_from_pickle(value: &Value) -> Result<Array2<f32>, String> {
+    let state = match value {
+        Value::Object { state, .. } if matches!(**state, Value::Dict(_)) => state,
+        _ => {
+            return value
+                .to_ndarray()?
+                .to_f64()?
+                .mapv(|x| x as f32)
+                .into_dimensionality()
+                .map_err(|e| format!("Expected a matrix: {:?}", e))
+        }
+    };
+    let field = |key: &str| {
+        state
+            .get(key)
+            .ok_or_else(|| format!("Sparse matrix without {}", key))
+    };
+    let shape = field("_shape")?.to_int_vec()?;
+    let (rows, cols) = match shape.as_slice() {
+        &[rows, cols] => match (usize::try_from(rows), usize::try_from(cols)) {
+            (Ok(rows), Ok(cols)) => (rows, cols),
+            _ => return Err(format!("Sparse matrix of shape {:?}", shape)),
+        },
+        _ => return Err(format!("Sparse matrix of shape {:?}", shape)),
+    };
+    let indices = to_indices(field("indices")?)?;
+    let indptr = to_indices(field("indptr")?)?;
+    let data = field("data")?.to_ndarray()?.to_f64()?;
+    if indices.len() != data.len() {
+        return Err(format!(
+            "Sparse matrix with {} indices and {} values",
+            indices.len(),
+            data.len()
+        ));
+    }
+    let valid_indptr = indptr.len() == rows + 1
+        && indptr.first() == Some(&0)
+        && indptr.windows(2).all(|w| w[0] <= w[1])
+        && indptr[rows] <= indices.len();
+    if !valid_indptr {
+        return Err(format!(
+            "Invalid indptr {:?} of a sparse matrix with {} rows and {} values",
+            indptr,
+            rows,
+            indices.len()
+        ));
+    }
+    if let Some(&col) = indices.iter().find(|&&col| col >= cols) {
+        return Err(format!(
+            "Column index {} of a sparse matrix with {} columns",
+            col, cols
+        ));
+    }
+    let mut dense: Array2<f32> = Array2::zeros((rows, cols));
+    for row in 0..rows {
+        for k in indptr[row]..indptr[row + 1] {
+            dense[[row, indices[k]]] = data[k] as f32;
+        }
+    }
+    Ok(dense)
+}
+
+/// Elements of a pickled integer numpy array, which must not be negative.
+fn to_indices(value: &Value) -> Result<Vec<usize>, String> {
+    value
+        .to_ndarray()?
+        .to_i64()?
+        .iter()
+        .map(|&i| usize::try_from(i).map_err(|_| format!("Negative sparse matrix index {}", i)))
+        .collect()
+}
+
+/// Edges of a pickled `defaultdict(list)` or dict mapping every node to its neighbours.
+fn edges_from_pickle(value: &Value) -> Result<Vec<(usize, usize)>, String> {
+    let items = match value {
+        Value::Dict(items) | Value::Object { items, .. } => items,
+        _ => return Err("Expected a dict of adjacency lists".to_string()),
+    };
+    let mut edges = Vec::new();
+    let node_id = |id: i64| usize::try_from(id).map_err(|_| format!("Invalid node id {}", id));
+    for (node, neighbors) in items {
+        let node = node_id(node.as_int().ok_or("Expected an int node id")?)?;
+        for neighbor in neighbors.to_int_vec()? {
+            edges.push((node, node_id(neighbor)?));
+        }
+    }
+    Ok(edges)
+}
+
+/// Follows `load_data` of Kipf and Welling's GCN code: test nodes are stored in the order of
+/// `ind.<name>.test.index` and moved to their node ids, the training nodes are the first
+/// `len(y)` nodes and the validation nodes the 500 following ones. CiteSeer contains isolated
+/// test nodes without features or label, they get zero features and class 0.
+fn get_planetoid(base_path: &str, dataset: Planetoid) -> Result<Graph, String> {
+    let name = dataset.name();
+    let path = |suffix: &str| Path::new(base_path).join(format!("ind.{}.{}", name, suffix));
+    let load = |suffix: &str| pickle::load(&path(suffix));
+
+    let y_len = matrix_from_pickle(&load("y")?)?.nrows();
+    let allx = matrix_from_pickle(&load("allx")?)?;
+    let ally = matrix_from_pickle(&load("ally")?)?;
+    let tx = matrix_from_pickle(&load("tx")?)?;
+    let ty = matrix_from_pickle(&load("ty")?)?;
+    let edges = edges_from_pickle(&load("graph")?)?;
+    let test_index_path = path("test.index");
+    let test_index: Vec<usize> = fs::read_to_string(&test_index_path)
+        .map_err(|e| format!("Failed to read {:?}: {:?}", test_index_path, e))?
+        .split_whitespace()
+        .map(|i| i.parse().map_err(|_| format!("Invalid test index {:?}", i)))
+        .collect::<Result<_, _>>()?;
+
+    let mut sorted_test = test_index.clone();
+    sorted_test.sort_unstable();
+    let (first, last) = match (sorted_test.first(), sorted_test.last()) {
+        (Some(&first), Some(&last)) => (first, last),
+        _ => return Err("Empty test index".to_string()),
+    };
+    if first != allx.nrows() || tx.nrows() != test_index.len() {
+        return Err(format!(
+            "Test index range {}..={} doesn't match {} training and {} test nodes",
+            first,
+            last,
+            allx.nrows(),
+            tx.nrows()
+        ));
+    }
+    if y_len > allx.nrows()
+        || ally.nrows() != allx.nrows()
+        || ty.nrows() != tx.nrows()
+        || tx.ncols() != allx.ncols()
+        || ty.ncols() != ally.ncols()
+    {
+        return Err(format!(
+            "Inconsistent shapes of y ({} rows), allx {:?}, ally {:?}, tx {:?} and ty {:?}",
+            y_len,
+            allx.dim(),
+            ally.dim(),
+            tx.dim(),
+            ty.dim()
+        ));
+    }
+    let nodes = last + 1;
+    let mut features: Array2<f32> = Array2::zeros((nodes, allx.ncols()));
+    let mut lbl: Array2<f32> = Array2::zeros((nodes, ally.ncols()));
+    features.slice_mut(s![..allx.nrows(), ..]).assign(&allx);
+    lbl.slice_mut(s![..ally.nrows(), ..]).assign(&ally);
+    // Row i of tx belongs to node test_index[i].
+    for (i, &node) in test_index.iter().enumerate() {
+        features.row_mut(node).assign(&tx.row(i));
+        lbl.row_mut(node).assign(&ty.row(i));
+    }
+
+    let train: Vec<usize> = (0..y_len).collect();
+    let val: Vec<usize> = (y_len..(y_len + 500).min(nodes)).collect();
+    build_graph(
+        features,
+        Array1::from(lbl.classes()),
+        edges,
+        [&train, &val, &sorted_test],
+        Vec::new(),
+    )
+}
+
+/// Parse `cora.content` (`<paper id> <1433 binary word features> <class>`) and `cora.cites`
+/// (`<cited paper id> <citing paper id>`). Nodes keep the order of `cora.content`, the split
+/// is the one of Kipf's pygcn: nodes 0..140 for training, 200..500 for validation and
+/// 500..1500 for testing.
+fn get_cora_raw(base_path: &str) -> Result<Graph, String> {
+    let read = |file: &str| {
+        let path = Path::new(base_path).join(file);
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {:?}", path, e))
+    };
+    let content = read("cora.content")?;
+    let cites = read("cora.cites")?;
+
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    let mut values: Vec<f32> = Vec::new();
+    let mut classes: Vec<&str> = Vec::new();
+    let mut columns = None;
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 2 || *columns.get_or_insert(fields.len()) != fields.len() {
+            return Err(format!("Malformed line in cora.content: {:?}", line));
+        }
+        if ids.insert(fields[0], ids.len()).is_some() {
+            return Err(format!("Duplicate paper in cora.content: {:?}", line));
+        }
+        for field in &fields[1..fields.len() - 1] {
+            values.push(
+                field
+                    .parse()
+                    .map_err(|_| format!("Invalid feature {:?} in cora.content", field))?,
+            );
+        }
+        classes.push(fields[fields.len() - 1]);
+    }
+    let nodes = ids.len();
+    let features = Array2::from_shape_vec((nodes, values.len() / nodes.max(1)), values)
+        .map_err(|e| format!("Failed to read features: {:?}", e))?;
+
+    let mut class_names: Vec<String> = classes.iter().map(|c| c.to_string()).collect();
+    class_names.sort();
+    class_names.dedup();
+    let labels: Array1<usize> = classes
+        .iter()
+        .map(|c| class_names.binary_search_by(|n| n.as_str().cmp(c)).unwrap())
+        .collect();
+
+    let mut edges = Vec::new();
+    for line in cites.lines().filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [a, b] => match (ids.get(a), ids.get(b)) {
+                (Some(&a), Some(&b)) => edges.push((a, b)),
+                _ => return Err(format!("Unknown paper in cora.cites: {:?}", line)),
+            },
+            _ => return Err(format!("Malformed line in cora.cites: {:?}", line)),
+        }
+    }
+
+    let train: Vec<usize> = (0..140.min(nodes)).collect();
+    let val: Vec<usize> = (200.min(nodes)..500.min(nodes)).collect();
+    let test: Vec<usize> = (500.min(nodes)..1500.min(nodes)).collect();
+    build_graph(features, labels, edges, [&train, &val, &test], class_names)
+}
+
+/// Cora, CiteSeer and PubMed from the `ind.<name>.*` files of the Planetoid repository. The
+/// files don't name the classes, so `class_names` is empty.
+pub mod planetoid {
+    pub use super::{Graph, Planetoid};
+    static BASE_PATH: &str = "data/planetoid/";
+
+    pub fn new(dataset: Planetoid) -> Graph {
+        super::get_planetoid(BASE_PATH, dataset).unwrap()
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download(dataset: Planetoid) {
+        super::download::download_planetoid(BASE_PATH, dataset.name()).unwrap();
+    }
+}
+
+/// Cora from the original `cora.content` and `cora.cites` files.
+pub mod cora {
+    pub use super::Graph;
+    static BASE_PATH: &str = "data/cora/";
+
+    pub fn new() -> Graph {
+        super::get_cora_raw(BASE_PATH).unwrap()
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download_and_extract() {
+        super::download::download_and_extract_cora(BASE_PATH).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Planetoid files of 6 nodes as pickled by `pickle.dumps(x, protocol=2)`: `allx` and `tx`
+    // are `scipy.sparse.csr_matrix`, the labels numpy arrays and `graph` a `defaultdict(list)`.
+    // Nodes 0..4 are in `allx`, the rows of `tx` belong to the test nodes 5 and 4.
+    const Y: &[u8] = b"\x80\x02cnumpy.core.multiarray\n_reconstruct\nq\x00cnumpy\nndarray\nq\x01K\x00\x85q\x02c_codecs\nencode\nq\x03X\x01\x00\x00\x00bq\x04X\x06\x00\x00\x00latin1q\x05\x86q\x06Rq\x07\x87q\x08Rq\x09(K\x01K\x02K\x02\x86q\ncnumpy\ndtype\nq\x0bX\x02\x00\x00\x00i8q\x0c\x89\x88\x87q\x0dRq\x0e(K\x03X\x01\x00\x00\x00<q\x0fNNNJ\xff\xff\xff\xffJ\xff\xff\xff\xffK\x00tq\x10b\x89h\x03X \x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00q\x11h\x05\x86q\x12Rq\x13tq\x14b.";
+    const ALLX: &[u8] = b"\x80\x02cscipy.sparse.csr\ncsr_matrix\nq\x00)\x81q\x01}q\x02(X\x06\x00\x00\x00_shapeq\x03K\x04K\x03\x86q\x04X\x08\x00\x00\x00maxprintq\x05K2X\x06\x00\x00\x00formatq\x06X\x03\x00\x00\x00csrq\x07X\x04\x00\x00\x00dataq\x08cnumpy.core.multiarray\n_reconstruct\nq\x09cnumpy\nndarray\nq\nK\x00\x85q\x0bc_codecs\nencode\nq\x0cX\x01\x00\x00\x00bq\x0dX\x06\x00\x00\x00latin1q\x0e\x86q\x0fRq\x10\x87q\x11Rq\x12(K\x01K\x05\x85q\x13cnumpy\ndtype\nq\x14X\x02\x00\x00\x00f8q\x15\x89\x88\x87q\x16Rq\x17(K\x03X\x01\x00\x00\x00<q\x18NNNJ\xff\xff\xff\xffJ\xff\xff\xff\xffK\x00tq\x19b\x89h\x0cX+\x00\x00\x00\x00\x00\x00\x00\x00\x00\xc3\xb0?\x00\x00\x00\x00\x00\x00\x00@\x00\x00\x00\x00\x00\x00\x08@\x00\x00\x00\x00\x00\x00\xc3\xb0?\x00\x00\x00\x00\x00\x00\xc3\xb0?q\x1ah\x0e\x86q\x1bRq\x1ctq\x1dbX\x07\x00\x00\x00indicesq\x1eh\x09h\nh\x0bh\x10\x87q\x1fRq (K\x01K\x05\x85q!h\x14X\x02\x00\x00\x00i4q\x22\x89\x88\x87q#Rq$h\x19b\x89h\x0cX\x14\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x02\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00q%h\x0e\x86q&Rq'tq(bX\x06\x00\x00\x00indptrq)h\x09h\nh\x0bh\x10\x87q*Rq+(K\x01K\x05\x85q,h\x14h\x22\x89\x88\x87q-Rq.h\x19b\x89h\x0cX\x14\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x02\x00\x00\x00\x03\x00\x00\x00\x05\x00\x00\x00q/h\x0e\x86q0Rq1tq2bub.";
+    const ALLY: &[u8] = b"\x80\x02cnumpy.core.multiarray\n_reconstruct\nq\x00cnumpy\nndarray\nq\x01K\x00\x85q\x02c_codecs\nencode\nq\x03X\x01\x00\x00\x00bq\x04X\x06\x00\x00\x00latin1q\x05\x86q\x06Rq\x07\x87q\x08Rq\x09(K\x01K\x04K\x02\x86q\ncnumpy\ndtype\nq\x0bX\x02\x00\x00\x00i8q\x0c\x89\x88\x87q\x0dRq\x0e(K\x03X\x01\x00\x00\x00<q\x0fNNNJ\xff\xff\xff\xffJ\xff\xff\xff\xffK\x00tq\x10b\x89h\x03X@\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00q\x11h\x05\x86q\x12Rq\x13tq\x14b.";
+    const TX: &[u8] = b"\x80\x02cscipy.sparse.csr\ncsr_matrix\nq\x00)\x81q\x01}q\x02(X\x06\x00\x00\x00_shapeq\x03K\x02K\x03\x86q\x04X\x08\x00\x00\x00maxprintq\x05K2X\x06\x00\x00\x00formatq\x06X\x03\x00\x00\x00csrq\x07X\x04\x00\x00\x00dataq\x08cnumpy.core.multiarray\n_reconstruct\nq\x09cnumpy\nndarray\nq\nK\x00\x85q\x0bc_codecs\nencode\nq\x0cX\x01\x00\x00\x00bq\x0dX\x06\x00\x00\x00latin1q\x0e\x86q\x0fRq\x10\x87q\x11Rq\x12(K\x01K\x02\x85q\x13cnumpy\ndtype\nq\x14X\x02\x00\x00\x00f8q\x15\x89\x88\x87q\x16Rq\x17(K\x03X\x01\x00\x00\x00<q\x18NNNJ\xff\xff\xff\xffJ\xff\xff\xff\xffK\x00tq\x19b\x89h\x0cX\x10\x00\x00\x00\x00\x00\x00\x00\x00\x00\x14@\x00\x00\x00\x00\x00\x00\x10@q\x1ah\x0e\x86q\x1bRq\x1ctq\x1dbX\x07\x00\x00\x00indicesq\x1eh\x09h\nh\x0bh\x10\x87q\x1fRq (K\x01K\x02\x85q!h\x14X\x02\x00\x00\x00i4q\x22\x89\x88\x87q#Rq$h\x19b\x89h\x0cX\x08\x00\x00\x00\x02\x00\x00\x00\x00\x00\x00\x00q%h\x0e\x86q&Rq'tq(bX\x06\x00\x00\x00indptrq)h\x09h\nh\x0bh\x10\x87q*Rq+(K\x01K\x03\x85q,h\x14h\x22\x89\x88\x87q-Rq.h\x19b\x89h\x0cX\x0c\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x02\x00\x00\x00q/h\x0e\x86q0Rq1tq2bub.";
+    const TY: &[u8] = b"\x80\x02cnumpy.core.multiarray\n_reconstruct\nq\x00cnumpy\nndarray\nq\x01K\x00\x85q\x02c_codecs\nencode\nq\x03X\x01\x00\x00\x00bq\x04X\x06\x00\x00\x00latin1q\x05\x86q\x06Rq\x07\x87q\x08Rq\x09(K\x01K\x02K\x02\x86q\ncnumpy\ndtype\nq\x0bX\x02\x00\x00\x00i8q\x0c\x89\x88\x87q\x0dRq\x0e(K\x03X\x01\x00\x00\x00<q\x0fNNNJ\xff\xff\xff\xffJ\xff\xff\xff\xffK\x00tq\x10b\x89h\x03X \x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00q\x11h\x05\x86q\x12Rq\x13tq\x14b.";
+    const GRAPH: &[u8] = b"\x80\x02ccollections\ndefaultdict\nq\x00c__builtin__\nlist\nq\x01\x85q\x02Rq\x03(K\x00]q\x04(K\x01K\x02eK\x01]q\x05K\x00aK\x03]q\x06K\x05aK\x05]q\x07(K\x03K\x04eu.";
+
+    /// Directory for the files of a test, removed by `remove_dir_all` at the end.
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("graph-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn planetoid_files(name: &str) -> std::path::PathBuf {
+        let dir = temp_dir(name);
+        let files = [
+            ("y", Y),
+            ("allx", ALLX),
+            ("ally", ALLY),
+            ("tx", TX),
+            ("ty", TY),
+            ("graph", GRAPH),
+            ("test.index", b"5\n4\n"),
+        ];
+        for (suffix, bytes) in &files {
+            fs::write(dir.join(format!("ind.cora.{}", suffix)), bytes).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn planetoid() {
+        let dir = planetoid_files("planetoid");
+        let graph = get_planetoid(dir.to_str().unwrap(), Planetoid::Cora);
+        fs::remove_dir_all(&dir).unwrap();
+        let graph = graph.unwrap();
+        assert_eq!(
+            graph.features,
+            arr2(&[
+                [1., 0., 0.],
+                [0., 2., 0.],
+                [0., 0., 3.],
+                [1., 1., 0.],
+                [4., 0., 0.],
+                [0., 0., 5.]
+            ])
+        );
+        assert_eq!(graph.labels, arr1(&[0, 1, 0, 1, 1, 0]));
+        assert_eq!(graph.num_classes(), 2);
+        assert_eq!(
+            graph.edges,
+            vec![
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (2, 0),
+                (3, 5),
+                (4, 5),
+                (5, 3),
+                (5, 4)
+            ]
+        );
+        assert_eq!(graph.indptr, vec![0, 2, 3, 4, 5, 6, 8]);
+        assert_eq!(graph.neighbors(5), &[3, 4]);
+        assert_eq!(
+            graph.train_mask,
+            arr1(&[true, true, false, false, false, false])
+        );
+        assert_eq!(
+            graph.val_mask,
+            arr1(&[false, false, true, true, true, true])
+        );
+        assert_eq!(
+            graph.test_mask,
+            arr1(&[false, false, false, false, true, true])
+        );
+    }
+
+    #[test]
+    fn planetoid_with_unknown_nodes() {
+        let dir = planetoid_files("unknown-nodes");
+        fs::write(dir.join("ind.cora.test.index"), "5\n6\n").unwrap();
+        let wrong_index = get_planetoid(dir.to_str().unwrap(), Planetoid::Cora);
+        fs::write(dir.join("ind.cora.test.index"), "5\n4\n").unwrap();
+        // {0: [1, 9]}, protocol 2
+        fs::write(
+            dir.join("ind.cora.graph"),
+            b"\x80\x02}q\x00K\x00]q\x01(K\x01K\tes.",
+        )
+        .unwrap();
+        let wrong_edge = get_planetoid(dir.to_str().unwrap(), Planetoid::Cora);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(wrong_index.is_err());
+        assert_eq!(
+            wrong_edge.unwrap_err(),
+            "Edge (0, 9) of a graph with 6 nodes"
+        );
+    }
+
+    /// `matrix` with the state entry `key` replaced by `value`.
+    fn with_field(matrix: &Value, key: &str, value: Value) -> Value {
+        let mut matrix = matrix.clone();
+        if let Value::Object { state, .. } = &mut matrix {
+            if let Value::Dict(items) = &mut **state {
+                for (k, v) in items.iter_mut() {
+                    if k.as_str() == Some(key) {
+                        *v = value.clone();
+                    }
+                }
+            }
+        }
+        matrix
+    }
+
+    /// `array`, a pickled `int32` numpy array, holding `values` instead.
+    fn with_values(array: &Value, values: &[i32]) -> Value {
+        let mut array = array.clone();
+        if let Value::Object { state, .. } = &mut array {
+            if let Value::Tuple(state) = &mut **state {
+                state[1] = Value::Tuple(vec![Value::Int(values.len() as i64)]);
+                state[4] = Value::Bytes(values.iter().flat_map(|v| v.to_le_bytes()).collect());
+            }
+        }
+        array
+    }
+
+    #[test]
+    fn malformed_sparse_matrices() {
+        let allx = pickle::from_slice(ALLX).unwrap();
+        assert_eq!(matrix_from_pickle(&allx).unwrap().dim(), (4, 3));
+        let indptr = match &allx {
+            Value::Object { state, .. } => state.get("indptr").unwrap(),
+            _ => unreachable!(),
+        };
+        let shape = |rows, cols| Value::Tuple(vec![Value::Int(rows), Value::Int(cols)]);
+        let invalid = vec![
+            with_field(&allx, "_shape", shape(-4, 3)),
+            with_field(&allx, "_shape", shape(4, 2)),
+            with_field(&allx, "_shape", shape(5, 3)),
+            with_field(&allx, "indptr", with_values(indptr, &[1, 1, 2, 3, 5])),
+            with_field(&allx, "indptr", with_values(indptr, &[0, 2, 1, 3, 5])),
+            with_field(&allx, "indptr", with_values(indptr, &[0, 1, 2, 3, 6])),
+            with_field(&allx, "indptr", with_values(indptr, &[0, 1, 2, -3, 5])),
+            with_field(&allx, "indices", with_values(indptr, &[0, 1, 3, 0, 1])),
+            with_field(&allx, "indices", with_values(indptr, &[0, 1, 2, -1, 1])),
+        ];
+        for (i, matrix) in invalid.iter().enumerate() {
+            assert!(matrix_from_pickle(matrix).is_err(), "case {}", i);
+        }
+    }
+
+    const CONTENT: &str = "31336 0 1 0 Neural_Networks\n\
+                           1061127 1 0 1 Rule_Learning\n\
+                           1106406 1 1 0 Neural_Networks\n";
+
+    #[test]
+    fn cora_raw() {
+        let dir = temp_dir("cora");
+        fs::write(dir.join("cora.content"), CONTENT).unwrap();
+        fs::write(
+            dir.join("cora.cites"),
+            "31336 1061127\n1106406 31336\n31336 1061127\n",
+        )
+        .unwrap();
+        let graph = get_cora_raw(dir.to_str().unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+        let graph = graph.unwrap();
+        assert_eq!(
+            graph.features,
+            arr2(&[[0., 1., 0.], [1., 0., 1.], [1., 1., 0.]])
+        );
+        assert_eq!(graph.labels, arr1(&[0, 1, 0]));
+        assert_eq!(graph.class_names, vec!["Neural_Networks", "Rule_Learning"]);
+        assert_eq!(graph.edges, vec![(0, 1), (0, 2), (1, 0), (2, 0)]);
+        assert_eq!(graph.neighbors(0), &[1, 2]);
+        assert_eq!(graph.train_mask, arr1(&[true, true, true]));
+        assert!(!graph.val_mask.iter().any(|&m| m));
+    }
+
+    #[test]
+    fn cora_raw_errors() {
+        let dir = temp_dir("cora-errors");
+        let cases = [
+            (CONTENT, "31336 42\n"),
+            (CONTENT, "31336\n"),
+            ("31336 0 1 A\n31336 1 0 B\n", ""),
+            ("31336 0 1 A\n1061127 1 B\n", ""),
+            ("31336 0 x A\n", ""),
+        ];
+        let results: Vec<_> = cases
+            .iter()
+            .map(|(content, cites)| {
+                fs::write(dir.join("cora.content"), content).unwrap();
+                fs::write(dir.join("cora.cites"), cites).unwrap();
+                get_cora_raw(dir.to_str().unwrap())
+            })
+            .collect();
+        fs::remove_dir_all(&dir).unwrap();
+        for (result, case) in results.iter().zip(&cases) {
+            assert!(result.is_err(), "{:?}", case);
+        }
+    }
+}
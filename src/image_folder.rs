@@ -0,0 +1,254 @@
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView};
+use ndarray::prelude::*;
+use ndarray::{Array2, Array3, Array4};
+use rayon::prelude::*;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+static EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+
+/// Images and labels of a directory laid out as `root/<class>/<image>.png`.
+///
+/// Images use the same NCHW layout as `cifar_builder::Data` (with a single channel for
+/// grayscale images), labels are one-hot encoded and `classes[i]` is the folder name belonging
+/// to label index `i`.
+pub struct Data {
+    pub img: Array4<f32>,
+    pub lbl: Array2<f32>,
+    pub classes: Vec<String>,
+}
+
+/// Configures how the images of a class folder tree are decoded.
+///
+/// Class indices are derived from the sorted folder names below `root`. Without a call to
+/// `resize` or `resize_to_fill` all images need to have the same size.
+pub struct ImageFolder {
+    root: PathBuf,
+    size: Option<(usize, usize)>,
+    fill: bool,
+    parallel: bool,
+    normalized: bool,
+    grayscale: bool,
+}
+
+impl ImageFolder {
+    pub fn new<P: AsRef<Path>>(root: P) -> ImageFolder {
+        ImageFolder {
+            root: root.as_ref().to_path_buf(),
+            size: None,
+            fill: false,
+            parallel: true,
+            normalized: false,
+            grayscale: false,
+        }
+    }
+
+    /// Resize every image to `rows` x `cols` while decoding.
+    pub fn resize(mut self, rows: usize, cols: usize) -> ImageFolder {
+        self.size = Some((rows, cols));
+        self.fill = false;
+        self
+    }
+
+    /// Resize every image to `rows` x `cols` without distorting it: the image is scaled to
+    /// cover `rows` x `cols` and the overhanging part is cropped evenly on both sides.
+    pub fn resize_to_fill(mut self, rows: usize, cols: usize) -> ImageFolder {
+        self.size = Some((rows, cols));
+        self.fill = true;
+        self
+    }
+
+    /// Decode the images on all available cores (default) or sequentially.
+    pub fn parallel(mut self, parallel: bool) -> ImageFolder {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Scale pixel values the same way as `new_normalized` of the other datasets.
+    pub fn normalized(mut self, normalized: bool) -> ImageFolder {
+        self.normalized = normalized;
+        self
+    }
+
+    /// Decode into a single luminance channel instead of RGB.
+    pub fn grayscale(mut self, grayscale: bool) -> ImageFolder {
+        self.grayscale = grayscale;
+        self
+    }
+
+    /// Sorted names of all class folders.
+    pub fn classes(&self) -> Result<Vec<String>, String> {
+        let mut classes = Vec::new();
+        for entry in read_dir(&self.root)? {
+            if entry.is_dir() {
+                if let Some(name) = entry.file_name().and_then(|n| n.to_str()) {
+                    classes.push(name.to_string());
+                }
+            }
+        }
+        classes.sort();
+        Ok(classes)
+    }
+
+    /// Path and class index of every image, ordered by class and file name.
+    pub fn samples(&self) -> Result<Vec<(PathBuf, usize)>, String> {
+        let mut samples = Vec::new();
+        for (class, name) in self.classes()?.iter().enumerate() {
+            for path in image_paths(&self.root.join(name))? {
+                samples.push((path, class));
+            }
+        }
+        Ok(samples)
+    }
+
+    pub fn load(&self) -> Result<Data, String> {
+        let classes = self.classes()?;
+        let samples = self.samples()?;
+        let (paths, labels): (Vec<PathBuf>, Vec<usize>) = samples.into_iter().unzip();
+        let img = self.decode(&paths)?;
+        let lbl = one_hot(&labels, classes.len());
+        Ok(Data { img, lbl, classes })
+    }
+
+    /// Decode arbitrary image files with the settings of this loader.
+    pub(crate) fn decode(&self, paths: &[PathBuf]) -> Result<Array4<f32>, String> {
+        let (size, fill, grayscale) = (self.size, self.fill, self.grayscale);
+        let images: Vec<Array3<u8>> = if self.parallel {
+            paths
+                .par_iter()
+                .map(|path| decode_image(path, size, fill, grayscale))
+                .collect::<Result<_, _>>()?
+        } else {
+            paths
+                .iter()
+                .map(|path| decode_image(path, size, fill, grayscale))
+                .collect::<Result<_, _>>()?
+        };
+        let channels = if grayscale { 1 } else { 3 };
+
+        let (rows, cols) = match images.first() {
+            Some(image) => (image.shape()[1], image.shape()[2]),
+            None => size.unwrap_or((0, 0)),
+        };
+        let mut img: Array4<f32> = Array4::zeros((images.len(), channels, rows, cols));
+        for (i, (image, path)) in images.iter().zip(paths).enumerate() {
+            if image.shape() != [channels, rows, cols] {
+                return Err(format!(
+                    "Image {:?} has shape {:?}, expected {:?}. Use `resize` for images of different sizes.",
+                    path,
+                    image.shape(),
+                    [channels, rows, cols]
+                ));
+            }
+            img.index_axis_mut(Axis(0), i)
+                .zip_mut_with(image, |x, &p| *x = p as f32);
+        }
+        if self.normalized {
+            img.mapv_inplace(|x| x / 256.);
+        }
+        Ok(img)
+    }
+}
+
+/// Decode a single image into CHW layout.
+fn decode_image(
+    path: &Path,
+    size: Option<(usize, usize)>,
+    fill: bool,
+    grayscale: bool,
+) -> Result<Array3<u8>, String> {
+    let image =
+        image::open(path).map_err(|e| format!("Failed to decode image {:?}: {:?}", path, e))?;
+    let resize = |image: DynamicImage| match size {
+        Some((rows, cols)) if image.dimensions() != (cols as u32, rows as u32) => {
+            if fill {
+                image.resize_to_fill(cols as u32, rows as u32, FilterType::Triangle)
+            } else {
+                image.resize_exact(cols as u32, rows as u32, FilterType::Triangle)
+            }
+        }
+        _ => image,
+    };
+    let (cols, rows, channels, pixels) = if grayscale {
+        let luma = resize(DynamicImage::ImageLuma8(image.to_luma8())).to_luma8();
+        let (cols, rows) = luma.dimensions();
+        (cols, rows, 1, luma.into_raw())
+    } else {
+        let rgb = resize(DynamicImage::ImageRgb8(image.to_rgb8())).to_rgb8();
+        let (cols, rows) = rgb.dimensions();
+        (cols, rows, 3, rgb.into_raw())
+    };
+    let hwc = Array3::from_shape_vec((rows as usize, cols as usize, channels), pixels)
+        .map_err(|e| format!("Failed to read pixels of {:?}: {:?}", path, e))?;
+    Ok(hwc.permuted_axes([2, 0, 1]).as_standard_layout().to_owned())
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read directory {:?}: {:?}", dir, e))?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory {:?}: {:?}", dir, e))?;
+        paths.push(entry.path());
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Sorted paths of all png/jpeg files in `dir`.
+pub(crate) fn image_paths(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    Ok(read_dir(dir)?
+        .into_iter()
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+                .unwrap_or(false)
+        })
+        .collect())
+}
+
+pub(crate) fn one_hot(labels: &[usize], classes: usize) -> Array2<f32> {
+    let mut lbl: Array2<f32> = Array2::zeros((labels.len(), classes));
+    for (i, &label) in labels.iter().enumerate() {
+        lbl[[i, label]] = 1.;
+    }
+    lbl
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn resize_modes() {
+        let dir = std::env::temp_dir().join(format!("image-folder-{}", std::process::id()));
+        fs::create_dir_all(dir.join("wide")).unwrap();
+        // 40 x 20 pixels, green apart from red and blue stripes at the left and right border.
+        let image = RgbImage::from_fn(40, 20, |x, _| match x {
+            0..=7 => Rgb([255, 0, 0]),
+            8..=31 => Rgb([0, 255, 0]),
+            _ => Rgb([0, 0, 255]),
+        });
+        image.save(dir.join("wide").join("0.png")).unwrap();
+        let filled = ImageFolder::new(&dir).resize_to_fill(10, 10).load();
+        let stretched = ImageFolder::new(&dir).resize(10, 10).load();
+        let original = ImageFolder::new(&dir).load();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let filled = filled.unwrap();
+        assert_eq!(filled.classes, vec!["wide"]);
+        assert_eq!(filled.img.dim(), (1, 3, 10, 10));
+        // Only the green center is left after cropping the sides.
+        let green = filled.img.index_axis(Axis(1), 1);
+        assert!(green.iter().all(|&x| x == 255.));
+        assert!(filled.img.index_axis(Axis(1), 0).iter().all(|&x| x == 0.));
+        let stretched = stretched.unwrap();
+        assert_eq!(stretched.img.dim(), (1, 3, 10, 10));
+        assert_eq!(stretched.img[[0, 0, 5, 0]], 255.);
+        assert_eq!(original.unwrap().img.dim(), (1, 3, 20, 40));
+    }
+}
@@ -0,0 +1,40 @@
+use std::path::Path;
+
+use crate::download_helper::downloader;
+
+const TINY_BASE_URL: &str = "http://cs231n.stanford.edu";
+const TINY_ARCHIVE: &str = "tiny-imagenet-200.zip";
+const FASTAI_BASE_URL: &str = "https://s3.amazonaws.com/fast-ai-imageclas";
+
+pub fn download_and_extract_tiny(base_path: &str) -> Result<(), String> {
+    println!("Attempting to download and extract {}...", TINY_ARCHIVE);
+    downloader::download(base_path, TINY_BASE_URL.to_string(), vec![TINY_ARCHIVE])?;
+    let base_dir = Path::new(base_path);
+    if base_dir.join("wnids.txt").exists() {
+        println!(
+            "  Dataset already extracted to {:?}, skipping extraction.",
+            base_dir
+        );
+        return Ok(());
+    }
+    downloader::extract_zip(&base_dir.join(TINY_ARCHIVE), Path::new("data"))?;
+    println!("done unpacking .zip");
+    Ok(())
+}
+
+/// Download one of the fast.ai Imagenette/Imagewoof tarballs, e.g. `imagenette2-160.tgz`.
+pub fn download_and_extract_fastai(base_path: &str, archive: &str) -> Result<(), String> {
+    println!("Attempting to download and extract {}...", archive);
+    downloader::download(base_path, FASTAI_BASE_URL.to_string(), vec![archive])?;
+    let base_dir = Path::new(base_path);
+    if base_dir.join("train").exists() {
+        println!(
+            "  Dataset already extracted to {:?}, skipping extraction.",
+            base_dir
+        );
+        return Ok(());
+    }
+    downloader::extract_tar_gz(&base_dir.join(archive), Path::new("data"))?;
+    println!("done unpacking .tgz");
+    Ok(())
+}
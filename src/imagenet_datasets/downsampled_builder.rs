@@ -0,0 +1,94 @@
+//! Downsampled ImageNet (Chrabaszcz et al., 2017) in the pickled batch format shared with the
+//! CIFAR python distribution.
+//!
+//! The archives are only available after registering at image-net.org, so there is no
+//! `download_and_extract`. Place the extracted `train_data_batch_1` ... `train_data_batch_10`
+//! and `val_data` files in `data/imagenet32/` respectively `data/imagenet64/`.
+
+use ndarray::prelude::*;
+use ndarray::{Array2, Array4};
+
+use std::error::Error;
+
+use crate::cifar_datasets::cifar_builder::read_pickle_batches;
+pub use crate::cifar_datasets::cifar_builder::Data;
+
+static CLASSES: usize = 1000;
+static TRN_BATCHES: &[usize] = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+static TST_FILENAME: &str = "val_data";
+
+fn pickle2data(
+    pickle_paths: &[&str],
+    base_path: &str,
+    size: usize,
+) -> Result<(Array4<u8>, Array2<f32>), Box<dyn Error>> {
+    let (data, labels) = read_pickle_batches(pickle_paths, base_path, "labels")?;
+    let mut one_hot: Array2<f32> = Array2::zeros((labels.len(), CLASSES));
+    for (num, &label) in labels.iter().enumerate() {
+        // Labels are stored as 1 to 1000.
+        if label < 1 || label as usize > CLASSES {
+            return Err(format!(
+                "Label is {}, which is inconsistent with the ImageNet scheme",
+                label
+            )
+            .into());
+        }
+        one_hot[[num, label as usize - 1]] = 1.;
+    }
+    let data: Array4<u8> = Array::from_shape_vec((labels.len(), 3, size, size), data)?;
+    Ok((data, one_hot))
+}
+
+fn get_dataset(base_path: &str, size: usize, batches: &[usize], normalized: bool) -> Data {
+    let trn_paths: Vec<String> = batches
+        .iter()
+        .map(|i| format!("train_data_batch_{}", i))
+        .collect();
+    let trn_paths: Vec<&str> = trn_paths.iter().map(|p| p.as_str()).collect();
+
+    let (trn_img, trn_lbl) = pickle2data(&trn_paths, base_path, size).unwrap();
+    let (tst_img, tst_lbl) = pickle2data(&[TST_FILENAME], base_path, size).unwrap();
+
+    let mut trn_img = trn_img.mapv(|x| x as f32);
+    let mut tst_img = tst_img.mapv(|x| x as f32);
+    if normalized {
+        trn_img.mapv_inplace(|x| x / 256.);
+        tst_img.mapv_inplace(|x| x / 256.);
+    }
+    Data {
+        trn_img,
+        trn_lbl,
+        tst_img,
+        tst_lbl,
+    }
+}
+
+pub mod imagenet32 {
+    pub use super::Data;
+    static BASE_PATH: &str = "data/imagenet32/";
+    pub fn new() -> Data {
+        super::get_dataset(BASE_PATH, 32, super::TRN_BATCHES, false)
+    }
+    pub fn new_normalized() -> Data {
+        super::get_dataset(BASE_PATH, 32, super::TRN_BATCHES, true)
+    }
+    /// Only load the given training batches (1 to 10), the full set needs about 16 GB.
+    pub fn new_with_batches(batches: &[usize], normalized: bool) -> Data {
+        super::get_dataset(BASE_PATH, 32, batches, normalized)
+    }
+}
+
+pub mod imagenet64 {
+    pub use super::Data;
+    static BASE_PATH: &str = "data/imagenet64/";
+    pub fn new() -> Data {
+        super::get_dataset(BASE_PATH, 64, super::TRN_BATCHES, false)
+    }
+    pub fn new_normalized() -> Data {
+        super::get_dataset(BASE_PATH, 64, super::TRN_BATCHES, true)
+    }
+    /// Only load the given training batches (1 to 10), the full set needs about 63 GB.
+    pub fn new_with_batches(batches: &[usize], normalized: bool) -> Data {
+        super::get_dataset(BASE_PATH, 64, batches, normalized)
+    }
+}
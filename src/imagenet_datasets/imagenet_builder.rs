@@ -0,0 +1,252 @@
+use ndarray::{Array2, Array4};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "download")]
+use super::download;
+use crate::image_folder::{self, ImageFolder};
+
+static WNIDS_FILENAME: &str = "wnids.txt";
+static WORDS_FILENAME: &str = "words.txt";
+static VAL_ANNOTATIONS_FILENAME: &str = "val/val_annotations.txt";
+
+/// ImageNet subsets in NCHW layout with one-hot labels.
+///
+/// `wnids[i]` is the WordNet id of label index `i` and `class_names[i]` its human readable name.
+/// The test labels of Tiny ImageNet are not public, so its `tst_*` fields hold the official
+/// validation set. The same holds for the `val` folder of Imagenette and Imagewoof.
+pub struct Data {
+    pub trn_img: Array4<f32>,
+    pub trn_lbl: Array2<f32>,
+    pub tst_img: Array4<f32>,
+    pub tst_lbl: Array2<f32>,
+    pub wnids: Vec<String>,
+    pub class_names: Vec<String>,
+}
+
+fn read_lines(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap_or_else(|_| panic!("Unable to read {:?}.", path))
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Parse `words.txt`, which maps every WordNet id to a comma separated list of synonyms.
+fn wordnet_names(path: &Path, wnids: &[String]) -> Vec<String> {
+    let mut words: HashMap<String, String> = HashMap::new();
+    for line in read_lines(path) {
+        let mut parts = line.splitn(2, '\t');
+        if let (Some(wnid), Some(name)) = (parts.next(), parts.next()) {
+            words.insert(wnid.to_string(), name.trim().to_string());
+        }
+    }
+    wnids
+        .iter()
+        .map(|wnid| words.get(wnid).cloned().unwrap_or_else(|| wnid.clone()))
+        .collect()
+}
+
+fn get_tiny_imagenet(base_path: &str, normalized: bool) -> Data {
+    let base_path = Path::new(base_path);
+    let mut wnids = read_lines(&base_path.join(WNIDS_FILENAME));
+    wnids.sort();
+    let class_names = wordnet_names(&base_path.join(WORDS_FILENAME), &wnids);
+    let class_index: HashMap<&str, usize> = wnids
+        .iter()
+        .enumerate()
+        .map(|(i, wnid)| (wnid.as_str(), i))
+        .collect();
+
+    // Training images live in `train/<wnid>/images/`, next to a bounding box file.
+    let mut trn_paths: Vec<PathBuf> = Vec::new();
+    let mut trn_labels: Vec<usize> = Vec::new();
+    for (i, wnid) in wnids.iter().enumerate() {
+        let dir = base_path.join("train").join(wnid).join("images");
+        for path in image_folder::image_paths(&dir).unwrap() {
+            trn_paths.push(path);
+            trn_labels.push(i);
+        }
+    }
+
+    // Validation images share a single folder, their classes are listed in
+    // `val_annotations.txt` as `<file>\t<wnid>\t<x0>\t<y0>\t<x1>\t<y1>`.
+    let mut tst_paths: Vec<PathBuf> = Vec::new();
+    let mut tst_labels: Vec<usize> = Vec::new();
+    for line in read_lines(&base_path.join(VAL_ANNOTATIONS_FILENAME)) {
+        let mut parts = line.split('\t');
+        let (file, wnid) = match (parts.next(), parts.next()) {
+            (Some(file), Some(wnid)) => (file, wnid),
+            _ => panic!("Malformed line in {}: {:?}", VAL_ANNOTATIONS_FILENAME, line),
+        };
+        let label = *class_index
+            .get(wnid)
+            .unwrap_or_else(|| panic!("Unknown WordNet id {} in validation set.", wnid));
+        tst_paths.push(base_path.join("val").join("images").join(file));
+        tst_labels.push(label);
+    }
+
+    let loader = ImageFolder::new(base_path).normalized(normalized);
+    let trn_img = loader.decode(&trn_paths).unwrap();
+    let tst_img = loader.decode(&tst_paths).unwrap();
+    Data {
+        trn_img,
+        trn_lbl: image_folder::one_hot(&trn_labels, wnids.len()),
+        tst_img,
+        tst_lbl: image_folder::one_hot(&tst_labels, wnids.len()),
+        wnids,
+        class_names,
+    }
+}
+
+/// Available downsampled versions of Imagenette and Imagewoof.
+///
+/// The images of these versions only share their shortest side. During loading they are
+/// scaled to that size and the longer side is center cropped, giving square images.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Resolution {
+    Px160,
+    Px320,
+}
+
+impl Resolution {
+    fn size(self) -> usize {
+        match self {
+            Resolution::Px160 => 160,
+            Resolution::Px320 => 320,
+        }
+    }
+}
+
+fn get_imagenette(
+    base_path: &str,
+    resolution: Resolution,
+    normalized: bool,
+    names: &[(&str, &str)],
+) -> Data {
+    let size = resolution.size();
+    let load = |split: &str| {
+        ImageFolder::new(Path::new(base_path).join(split))
+            .resize_to_fill(size, size)
+            .normalized(normalized)
+            .load()
+            .unwrap()
+    };
+    let trn = load("train");
+    let tst = load("val");
+    assert_eq!(
+        trn.classes, tst.classes,
+        "Train and validation folders of {} contain different classes.",
+        base_path
+    );
+    Data {
+        trn_img: trn.img,
+        trn_lbl: trn.lbl,
+        tst_img: tst.img,
+        tst_lbl: tst.lbl,
+        class_names: trn
+            .classes
+            .iter()
+            .map(|wnid| match names.iter().find(|(id, _)| id == wnid) {
+                Some((_, name)) => name.to_string(),
+                None => wnid.clone(),
+            })
+            .collect(),
+        wnids: trn.classes,
+    }
+}
+
+pub mod tiny_imagenet {
+    pub use super::Data;
+    static BASE_PATH: &str = "data/tiny-imagenet-200/";
+    pub fn new() -> Data {
+        super::get_tiny_imagenet(BASE_PATH, false)
+    }
+    pub fn new_normalized() -> Data {
+        super::get_tiny_imagenet(BASE_PATH, true)
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download_and_extract() {
+        super::download::download_and_extract_tiny(BASE_PATH).unwrap();
+    }
+}
+
+pub mod imagenette {
+    pub use super::{Data, Resolution};
+    static CLASS_NAMES: &[(&str, &str)] = &[
+        ("n01440764", "tench"),
+        ("n02102040", "English springer"),
+        ("n02979186", "cassette player"),
+        ("n03000684", "chain saw"),
+        ("n03028079", "church"),
+        ("n03394916", "French horn"),
+        ("n03417042", "garbage truck"),
+        ("n03425413", "gas pump"),
+        ("n03445777", "golf ball"),
+        ("n03888605", "parachute"),
+    ];
+
+    fn base_path(resolution: Resolution) -> &'static str {
+        match resolution {
+            Resolution::Px160 => "data/imagenette2-160/",
+            Resolution::Px320 => "data/imagenette2-320/",
+        }
+    }
+    pub fn new(resolution: Resolution) -> Data {
+        super::get_imagenette(base_path(resolution), resolution, false, CLASS_NAMES)
+    }
+    pub fn new_normalized(resolution: Resolution) -> Data {
+        super::get_imagenette(base_path(resolution), resolution, true, CLASS_NAMES)
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download_and_extract(resolution: Resolution) {
+        let archive = match resolution {
+            Resolution::Px160 => "imagenette2-160.tgz",
+            Resolution::Px320 => "imagenette2-320.tgz",
+        };
+        super::download::download_and_extract_fastai(base_path(resolution), archive).unwrap();
+    }
+}
+
+pub mod imagewoof {
+    pub use super::{Data, Resolution};
+    static CLASS_NAMES: &[(&str, &str)] = &[
+        ("n02086240", "Shih-Tzu"),
+        ("n02087394", "Rhodesian ridgeback"),
+        ("n02088364", "beagle"),
+        ("n02089973", "English foxhound"),
+        ("n02093754", "Border terrier"),
+        ("n02096294", "Australian terrier"),
+        ("n02099601", "golden retriever"),
+        ("n02105641", "Old English sheepdog"),
+        ("n02111889", "Samoyed"),
+        ("n02115641", "dingo"),
+    ];
+
+    fn base_path(resolution: Resolution) -> &'static str {
+        match resolution {
+            Resolution::Px160 => "data/imagewoof2-160/",
+            Resolution::Px320 => "data/imagewoof2-320/",
+        }
+    }
+    pub fn new(resolution: Resolution) -> Data {
+        super::get_imagenette(base_path(resolution), resolution, false, CLASS_NAMES)
+    }
+    pub fn new_normalized(resolution: Resolution) -> Data {
+        super::get_imagenette(base_path(resolution), resolution, true, CLASS_NAMES)
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download_and_extract(resolution: Resolution) {
+        let archive = match resolution {
+            Resolution::Px160 => "imagewoof2-160.tgz",
+            Resolution::Px320 => "imagewoof2-320.tgz",
+        };
+        super::download::download_and_extract_fastai(base_path(resolution), archive).unwrap();
+    }
+}
@@ -0,0 +1,10 @@
+pub mod downsampled_builder;
+pub mod imagenet_builder;
+pub use downsampled_builder::imagenet32;
+pub use downsampled_builder::imagenet64;
+pub use imagenet_builder::imagenette;
+pub use imagenet_builder::imagewoof;
+pub use imagenet_builder::tiny_imagenet;
+
+#[cfg(feature = "download")]
+mod download;
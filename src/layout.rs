@@ -0,0 +1,90 @@
+//! Memory layouts of image batches.
+//!
+//! The loaders return NCHW images (CIFAR, ImageNet) or NHW images (MNIST and friends).
+//! `new_with_layout` of `cifar10`, `cifar100`, `mnist` and `mnist_fashion` arranges the images
+//! while loading instead, the dynamic dimension can be fixed without a copy:
+//!
+//! ```no_run
+//! use datasets::cifar10;
+//! use datasets::layout::Layout;
+//! use ndarray::{Ix2, Ix4};
+//!
+//! // Channels-last images for a model exported from TensorFlow ...
+//! let data = cifar10::new_with_layout(Layout::Nhwc, true);
+//! let trn_img = data.trn_img.into_dimensionality::<Ix4>().unwrap();
+//! assert_eq!(trn_img.shape(), [50_000, 32, 32, 3]);
+//! // ... and flat feature vectors for an MLP.
+//! let data = cifar10::new_with_layout(Layout::Flat, true);
+//! let trn_img = data.trn_img.into_dimensionality::<Ix2>().unwrap();
+//! assert_eq!(trn_img.shape(), [50_000, 3072]);
+//! ```
+
+use ndarray::prelude::*;
+use ndarray::IxDyn;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// `(n, channels, rows, cols)`, grayscale images get a single channel.
+    Nchw,
+    /// `(n, rows, cols, channels)`, grayscale images get a single channel.
+    Nhwc,
+    /// `(n, features)` with the features of every image in CHW order.
+    Flat,
+}
+
+impl Layout {
+    /// Arrange NCHW images while converting every pixel with `f`, in a single pass.
+    pub(crate) fn map_nchw<A, F>(self, images: &Array4<A>, f: F) -> ArrayD<f32>
+    where
+        A: Copy,
+        F: Fn(A) -> f32,
+    {
+        match self {
+            Layout::Nhwc => {
+                // `mapv` would keep the strides of the permuted view, so collect in logical
+                // order to get channels-last memory.
+                let nhwc = images.view().permuted_axes([0, 2, 3, 1]);
+                let pixels = nhwc.iter().map(|&x| f(x)).collect();
+                ArrayD::from_shape_vec(nhwc.shape(), pixels).unwrap()
+            }
+            Layout::Nchw | Layout::Flat => self.apply_nchw(images.mapv(f)),
+        }
+    }
+
+    /// Arrange NCHW images. Only `Nhwc` copies the pixels.
+    pub fn apply_nchw(self, images: Array4<f32>) -> ArrayD<f32> {
+        let (n, channels, rows, cols) = images.dim();
+        match self {
+            Layout::Nchw => images.into_dyn(),
+            Layout::Nhwc => images
+                .permuted_axes([0, 2, 3, 1])
+                .as_standard_layout()
+                .into_owned()
+                .into_dyn(),
+            Layout::Flat => standard_layout(images)
+                .into_shape(IxDyn(&[n, channels * rows * cols]))
+                .unwrap(),
+        }
+    }
+
+    /// Arrange grayscale NHW images. The pixels are never copied if `images` is in standard
+    /// layout.
+    pub fn apply_nhw(self, images: Array3<f32>) -> ArrayD<f32> {
+        let (n, rows, cols) = images.dim();
+        let shape = match self {
+            Layout::Nchw => vec![n, 1, rows, cols],
+            Layout::Nhwc => vec![n, rows, cols, 1],
+            Layout::Flat => vec![n, rows * cols],
+        };
+        standard_layout(images).into_shape(IxDyn(&shape)).unwrap()
+    }
+}
+
+/// `images` itself if it is in standard layout, otherwise a standard layout copy.
+fn standard_layout<D: Dimension>(images: Array<f32, D>) -> Array<f32, D> {
+    if images.is_standard_layout() {
+        images
+    } else {
+        images.as_standard_layout().into_owned()
+    }
+}
@@ -1,8 +1,42 @@
 mod cifar_datasets;
+mod imagenet_datasets;
 mod mnist_datasets;
+mod omniglot_datasets;
+mod quickdraw_datasets;
+mod speech_commands_datasets;
+mod usps_datasets;
+
+mod sampling;
 
 #[cfg(feature = "download")]
 mod download_helper;
 
-pub use cifar_datasets::cifar_builder::{cifar10, cifar100};
-pub use mnist_datasets::mnist_builder::{mnist, mnist_fashion};
+pub mod batches;
+pub mod color;
+pub mod csv_images;
+pub mod dataset;
+pub mod few_shot;
+pub mod graph;
+pub mod image_folder;
+pub mod layout;
+pub mod libsvm;
+pub mod lm;
+pub mod long_tail;
+pub mod npy;
+pub mod pickle;
+pub mod preprocess;
+pub mod resize;
+pub mod split;
+pub mod subset;
+pub mod synthetic;
+pub mod tabular;
+pub mod text;
+pub mod transforms;
+
+pub use cifar_datasets::{cifar10, cifar100};
+pub use imagenet_datasets::{imagenet32, imagenet64, imagenette, imagewoof, tiny_imagenet};
+pub use mnist_datasets::{mnist, mnist_fashion};
+pub use omniglot_datasets::omniglot;
+pub use quickdraw_datasets::quickdraw;
+pub use speech_commands_datasets::speech_commands;
+pub use usps_datasets::usps;
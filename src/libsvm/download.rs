@@ -0,0 +1,25 @@
+use std::path::Path;
+
+use crate::download_helper::downloader;
+
+const BASE_URL: &str = "https://www.csie.ntu.edu.tw/~cjlin/libsvmtools/datasets";
+
+/// Download `files` from a category of the LIBSVM repository, e.g. `binary`.
+pub fn download(base_path: &str, category: &str, files: &[&str]) -> Result<(), String> {
+    println!("Attempting to download {:?}...", files);
+    let base_url = format!("{}/{}", BASE_URL, category);
+    downloader::download(base_path, base_url, files.to_vec())
+}
+
+/// Download and decompress the `.bz2` compressed `archives`.
+pub fn download_and_extract(
+    base_path: &str,
+    category: &str,
+    archives: &[&str],
+) -> Result<(), String> {
+    download(base_path, category, archives)?;
+    for archive in archives {
+        downloader::extract_bz2(&Path::new(base_path).join(archive))?;
+    }
+    Ok(())
+}
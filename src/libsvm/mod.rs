@@ -0,0 +1,313 @@
+//! Reader for the sparse LIBSVM/SVMlight format and loaders for some datasets of the
+//! [LIBSVM repository](https://www.csie.ntu.edu.tw/~cjlin/libsvmtools/datasets/).
+//!
+//! Every line holds one sample as `<label> [qid:<id>] <index>:<value> ...`, optionally followed
+//! by a `#` comment. Indices are 1-based in the files and 0-based once loaded, features which
+//! are not listed are zero.
+
+use ndarray::Array2;
+
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+#[cfg(feature = "download")]
+mod download;
+
+/// Labels and features of a LIBSVM file, with the features in CSR layout.
+///
+/// The features of sample `i` are `values[indptr[i]..indptr[i + 1]]` at the (sorted) columns
+/// `indices[indptr[i]..indptr[i + 1]]`. `qid` is only set if the file contains query ids.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Data {
+    pub labels: Vec<f64>,
+    pub qid: Option<Vec<u64>>,
+    pub indices: Vec<usize>,
+    pub indptr: Vec<usize>,
+    pub values: Vec<f64>,
+    pub n_features: usize,
+}
+
+/// The training and test file of a dataset, sharing the same number of features.
+#[derive(Clone, Debug)]
+pub struct TrainTest {
+    pub trn: Data,
+    pub tst: Data,
+}
+
+impl Data {
+    /// Number of samples.
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Number of stored (non-zero) features.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Columns and values of the features of sample `i`.
+    pub fn row(&self, i: usize) -> (&[usize], &[f64]) {
+        let range = self.indptr[i]..self.indptr[i + 1];
+        (&self.indices[range.clone()], &self.values[range])
+    }
+
+    /// Treat the data as having `n_features` columns, e.g. to align a test file whose highest
+    /// feature index is lower than the one of the training file.
+    pub fn with_n_features(mut self, n_features: usize) -> Result<Data, String> {
+        if n_features < self.n_features {
+            return Err(format!(
+                "Data uses {} features, cannot shrink it to {}.",
+                self.n_features, n_features
+            ));
+        }
+        self.n_features = n_features;
+        Ok(self)
+    }
+
+    /// Dense `(samples, features)` matrix of the features.
+    pub fn to_dense(&self) -> Array2<f64> {
+        let mut dense: Array2<f64> = Array2::zeros((self.len(), self.n_features));
+        for (i, mut row) in dense.outer_iter_mut().enumerate() {
+            let (indices, values) = self.row(i);
+            for (&j, &value) in indices.iter().zip(values) {
+                row[j] = value;
+            }
+        }
+        dense
+    }
+
+    /// Sorted distinct labels.
+    pub fn classes(&self) -> Vec<f64> {
+        let mut classes = self.labels.clone();
+        classes.sort_by(f64::total_cmp);
+        classes.dedup_by(|a, b| a.total_cmp(b).is_eq());
+        classes
+    }
+
+    /// Index of every label into `classes()`.
+    pub fn class_indices(&self) -> Vec<usize> {
+        let classes = self.classes();
+        self.labels
+            .iter()
+            .map(|label| classes.binary_search_by(|c| c.total_cmp(label)).unwrap())
+            .collect()
+    }
+}
+
+/// Parse LIBSVM formatted lines from `reader`.
+pub fn read<R: BufRead>(reader: R) -> Result<Data, String> {
+    let mut data = Data {
+        indptr: vec![0],
+        ..Data::default()
+    };
+    let mut qids: Vec<u64> = Vec::new();
+    let mut row: Vec<(usize, f64)> = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read line {}: {:?}", number + 1, e))?;
+        let line = line.split('#').next().unwrap_or("");
+        let mut fields = line.split_whitespace();
+        let label = match fields.next() {
+            Some(label) => label,
+            None => continue,
+        };
+        let err = |what: &str, field: &str| {
+            format!("Invalid {} {:?} in line {}.", what, field, number + 1)
+        };
+        let value: f64 = label.parse().map_err(|_| err("label", label))?;
+        if !value.is_finite() {
+            return Err(err("label", label));
+        }
+        data.labels.push(value);
+
+        row.clear();
+        for field in fields {
+            let (key, value) = field.split_once(':').ok_or_else(|| err("feature", field))?;
+            if key == "qid" {
+                if !row.is_empty() {
+                    return Err(err("qid position of", field));
+                }
+                qids.resize(data.labels.len() - 1, 0);
+                qids.push(value.parse().map_err(|_| err("qid", field))?);
+                continue;
+            }
+            let index: usize = key.parse().map_err(|_| err("feature index", field))?;
+            if index == 0 {
+                return Err(err("feature index (indices start at 1)", field));
+            }
+            let value: f64 = value.parse().map_err(|_| err("feature value", field))?;
+            row.push((index - 1, value));
+        }
+        row.sort_by_key(|&(index, _)| index);
+        if row.windows(2).any(|w| w[0].0 == w[1].0) {
+            return Err(format!("Duplicate feature index in line {}.", number + 1));
+        }
+        if let Some(&(index, _)) = row.last() {
+            data.n_features = data.n_features.max(index + 1);
+        }
+        data.indices.extend(row.iter().map(|&(index, _)| index));
+        data.values.extend(row.iter().map(|&(_, value)| value));
+        data.indptr.push(data.indices.len());
+    }
+    if !qids.is_empty() {
+        qids.resize(data.labels.len(), 0);
+        data.qid = Some(qids);
+    }
+    Ok(data)
+}
+
+/// Parse a LIBSVM formatted string.
+pub fn parse(content: &str) -> Result<Data, String> {
+    read(content.as_bytes())
+}
+
+/// Read a LIBSVM formatted file.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Data, String> {
+    let path = path.as_ref();
+    let file =
+        fs::File::open(path).map_err(|e| format!("Failed to open file {:?}: {:?}", path, e))?;
+    read(BufReader::new(file)).map_err(|e| format!("Failed to parse {:?}: {}", path, e))
+}
+
+fn get_train_test(base_path: &str, trn_file: &str, tst_file: &str, n_features: usize) -> TrainTest {
+    let load = |file: &str| {
+        load(Path::new(base_path).join(file))
+            .and_then(|data| data.with_n_features(n_features))
+            .unwrap()
+    };
+    TrainTest {
+        trn: load(trn_file),
+        tst: load(tst_file),
+    }
+}
+
+/// Adult census income, binarized into 123 features. Labels are -1 and +1.
+pub mod a9a {
+    pub use super::{Data, TrainTest};
+    static BASE_PATH: &str = "data/libsvm/a9a/";
+    static FILES: &[&str] = &["a9a", "a9a.t"];
+    static FEATURES: usize = 123;
+
+    pub fn new() -> TrainTest {
+        super::get_train_test(BASE_PATH, FILES[0], FILES[1], FEATURES)
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download() {
+        super::download::download(BASE_PATH, "binary", FILES).unwrap();
+    }
+}
+
+/// RCV1 news articles as tf-idf vectors, binarized into CCAT and ECAT vs. GCAT and MCAT.
+/// Labels are -1 and +1.
+pub mod rcv1 {
+    pub use super::{Data, TrainTest};
+    static BASE_PATH: &str = "data/libsvm/rcv1/";
+    static ARCHIVES: &[&str] = &["rcv1_train.binary.bz2", "rcv1_test.binary.bz2"];
+    static FEATURES: usize = 47236;
+
+    pub fn new() -> TrainTest {
+        let (trn, tst) = (ARCHIVES[0], ARCHIVES[1]);
+        super::get_train_test(
+            BASE_PATH,
+            trn.trim_end_matches(".bz2"),
+            tst.trim_end_matches(".bz2"),
+            FEATURES,
+        )
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download_and_extract() {
+        super::download::download_and_extract(BASE_PATH, "binary", ARCHIVES).unwrap();
+    }
+}
+
+/// 20 Newsgroups as normalized bag of words. Labels are the newsgroups 1 to 20.
+pub mod news20 {
+    pub use super::{Data, TrainTest};
+    static BASE_PATH: &str = "data/libsvm/news20/";
+    static ARCHIVES: &[&str] = &["news20.bz2", "news20.t.bz2"];
+    static FEATURES: usize = 62061;
+
+    pub fn new() -> TrainTest {
+        let (trn, tst) = (ARCHIVES[0], ARCHIVES[1]);
+        super::get_train_test(
+            BASE_PATH,
+            trn.trim_end_matches(".bz2"),
+            tst.trim_end_matches(".bz2"),
+            FEATURES,
+        )
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download_and_extract() {
+        super::download::download_and_extract(BASE_PATH, "multiclass", ARCHIVES).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::arr2;
+
+    #[test]
+    fn sparse_rows_with_one_based_indices() {
+        let data =
+            parse("+1 3:0.5 1:2\n-1\n\n2 2:-1e-3 # trailing comment\n# comment only\n").unwrap();
+        assert_eq!(data.labels, vec![1., -1., 2.]);
+        assert_eq!(data.qid, None);
+        assert_eq!(data.indptr, vec![0, 2, 2, 3]);
+        assert_eq!(data.indices, vec![0, 2, 1]);
+        assert_eq!(data.values, vec![2., 0.5, -1e-3]);
+        assert_eq!(data.n_features, 3);
+        assert_eq!(data.row(1), (&[][..], &[][..]));
+        assert_eq!(
+            data.to_dense(),
+            arr2(&[[2., 0., 0.5], [0., 0., 0.], [0., -1e-3, 0.]])
+        );
+        assert_eq!(data.classes(), vec![-1., 1., 2.]);
+        assert_eq!(data.class_indices(), vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn query_ids() {
+        let data = parse("3 qid:1 1:1\n2 qid:1 2:1\n1 qid:7 1:0.5\n").unwrap();
+        assert_eq!(data.qid, Some(vec![1, 1, 7]));
+        assert_eq!(data.n_features, 2);
+        // Lines without a query id get id 0.
+        let data = parse("1 1:1\n2 qid:4 1:1\n3 1:1\n").unwrap();
+        assert_eq!(data.qid, Some(vec![0, 4, 0]));
+    }
+
+    #[test]
+    fn with_n_features() {
+        let data = parse("1 2:1\n").unwrap();
+        assert_eq!(
+            data.clone().with_n_features(5).unwrap().to_dense().dim(),
+            (1, 5)
+        );
+        assert!(data.with_n_features(1).is_err());
+    }
+
+    #[test]
+    fn invalid_lines() {
+        for content in &[
+            "nan 1:1",
+            "inf 1:1",
+            "one 1:1",
+            "1 0:1",
+            "1 1:1 1:2",
+            "1 1",
+            "1 a:1",
+            "1 1:x",
+            "1 1:1 qid:3",
+            "1 qid:-1 1:1",
+        ] {
+            assert!(parse(content).is_err(), "{:?}", content);
+        }
+    }
+}
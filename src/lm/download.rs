@@ -0,0 +1,42 @@
+use std::path::Path;
+
+use crate::download_helper::downloader;
+
+const TINY_SHAKESPEARE_BASE_URL: &str =
+    "https://raw.githubusercontent.com/karpathy/char-rnn/master/data/tinyshakespeare";
+const WIKITEXT2_BASE_URL: &str = "https://s3.amazonaws.com/research.metamind.io/wikitext";
+const WIKITEXT2_ARCHIVE: &str = "wikitext-2-v1.zip";
+const PTB_BASE_URL: &str = "https://raw.githubusercontent.com/wojzaremba/lstm/master/data";
+
+pub fn download_tiny_shakespeare(base_path: &str, file: &str) -> Result<(), String> {
+    println!("Attempting to download Tiny Shakespeare...");
+    downloader::download(base_path, TINY_SHAKESPEARE_BASE_URL.to_string(), vec![file])
+}
+
+pub fn download_and_extract_wikitext2(base_path: &str) -> Result<(), String> {
+    println!(
+        "Attempting to download and extract {}...",
+        WIKITEXT2_ARCHIVE
+    );
+    downloader::download(
+        base_path,
+        WIKITEXT2_BASE_URL.to_string(),
+        vec![WIKITEXT2_ARCHIVE],
+    )?;
+    let base_dir = Path::new(base_path);
+    if base_dir.join("wiki.train.tokens").exists() {
+        println!(
+            "  Dataset already extracted to {:?}, skipping extraction.",
+            base_dir
+        );
+        return Ok(());
+    }
+    downloader::extract_zip(&base_dir.join(WIKITEXT2_ARCHIVE), Path::new("data"))?;
+    println!("done unpacking .zip");
+    Ok(())
+}
+
+pub fn download_ptb(base_path: &str, files: &[&str]) -> Result<(), String> {
+    println!("Attempting to download the Penn Treebank...");
+    downloader::download(base_path, PTB_BASE_URL.to_string(), files.to_vec())
+}
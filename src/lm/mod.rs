@@ -0,0 +1,230 @@
+//! Language modeling corpora and a batched BPTT (backpropagation through time) iterator.
+//!
+//! ```no_run
+//! use datasets::lm::{ptb, Bptt, Level};
+//!
+//! let encoded = ptb::new().encode(Level::Word);
+//! for (input, target) in Bptt::new(&encoded.trn, 20, 35) {
+//!     assert_eq!(input.shape(), [20, 35]);
+//!     assert_eq!(input.slice(ndarray::s![.., 1..]), target.slice(ndarray::s![.., ..34]));
+//! }
+//! ```
+
+use ndarray::{s, Array2};
+
+use std::fs;
+use std::path::Path;
+
+use crate::text::{Vocab, VocabBuilder, UNK};
+
+#[cfg(feature = "download")]
+mod download;
+
+/// Token appended to every line of word-level corpora.
+pub const EOS: &str = "<eos>";
+
+/// Whether a corpus is split into characters or into whitespace separated words.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Level {
+    Char,
+    Word,
+}
+
+/// Raw text of the training, validation and test split of a corpus.
+#[derive(Clone, Debug)]
+pub struct Corpus {
+    pub trn: String,
+    pub val: String,
+    pub tst: String,
+}
+
+/// Token ids of all splits of a corpus and the vocabulary built from the training split.
+pub struct Encoded {
+    pub vocab: Vocab,
+    pub trn: Vec<u32>,
+    pub val: Vec<u32>,
+    pub tst: Vec<u32>,
+}
+
+/// Tokens of `text`. On word level every line, including empty ones, ends with `EOS`.
+pub fn tokenize(text: &str, level: Level) -> Vec<String> {
+    match level {
+        Level::Char => text.chars().map(|c| c.to_string()).collect(),
+        Level::Word => text
+            .lines()
+            .flat_map(|line| {
+                line.split_whitespace()
+                    .chain(std::iter::once(EOS))
+                    .map(|word| word.to_string())
+            })
+            .collect(),
+    }
+}
+
+impl Corpus {
+    /// Tokenize all splits and encode them with a vocabulary of all training tokens.
+    ///
+    /// Word-level vocabularies start with `EOS` and `UNK`, the latter replacing words missing
+    /// from the training split. Character-level vocabularies have no special tokens, so unseen
+    /// characters are dropped.
+    pub fn encode(&self, level: Level) -> Encoded {
+        let builder = match level {
+            Level::Char => VocabBuilder::new().specials(&[]),
+            Level::Word => VocabBuilder::new().specials(&[EOS, UNK]),
+        };
+        self.encode_with(level, &builder)
+    }
+
+    /// Like `encode`, but with a custom vocabulary, e.g. limited in size.
+    pub fn encode_with(&self, level: Level, builder: &VocabBuilder) -> Encoded {
+        let trn = tokenize(&self.trn, level);
+        let vocab = builder.build(std::slice::from_ref(&trn));
+        Encoded {
+            trn: vocab.encode(&trn),
+            val: vocab.encode(&tokenize(&self.val, level)),
+            tst: vocab.encode(&tokenize(&self.tst, level)),
+            vocab,
+        }
+    }
+}
+
+/// Iterates over `(input, target)` windows of a token stream for truncated BPTT.
+///
+/// The stream is cut into `batch_size` contiguous parts (dropping the remainder), which form
+/// the rows of a `(batch_size, len / batch_size)` matrix. Every window covers `seq_len`
+/// columns of it, the target is the input shifted by one token. Consecutive windows start
+/// `stride` columns apart, by default `seq_len`. Only full windows are returned.
+#[derive(Clone, Debug)]
+pub struct Bptt {
+    data: Array2<u32>,
+    seq_len: usize,
+    stride: usize,
+    pos: usize,
+}
+
+impl Bptt {
+    pub fn new(ids: &[u32], batch_size: usize, seq_len: usize) -> Bptt {
+        assert!(
+            batch_size > 0 && seq_len > 0,
+            "Batch size and sequence length must be positive."
+        );
+        let columns = ids.len() / batch_size;
+        let data =
+            Array2::from_shape_vec((batch_size, columns), ids[..batch_size * columns].to_vec())
+                .unwrap();
+        Bptt {
+            data,
+            seq_len,
+            stride: seq_len,
+            pos: 0,
+        }
+    }
+
+    /// Start consecutive windows `stride` tokens apart, e.g. less than `seq_len` for
+    /// overlapping windows.
+    pub fn stride(mut self, stride: usize) -> Bptt {
+        assert!(stride > 0, "Stride must be positive.");
+        self.stride = stride;
+        self
+    }
+
+    /// Number of windows per epoch.
+    pub fn num_windows(&self) -> usize {
+        let columns = self.data.shape()[1];
+        if columns < self.seq_len + 1 {
+            0
+        } else {
+            (columns - self.seq_len - 1) / self.stride + 1
+        }
+    }
+}
+
+impl Iterator for Bptt {
+    type Item = (Array2<u32>, Array2<u32>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.pos;
+        if start + self.seq_len + 1 > self.data.shape()[1] {
+            return None;
+        }
+        self.pos += self.stride;
+        let input = self.data.slice(s![.., start..start + self.seq_len]);
+        let target = self.data.slice(s![.., start + 1..start + self.seq_len + 1]);
+        Some((input.to_owned(), target.to_owned()))
+    }
+}
+
+fn read_text(path: &Path) -> String {
+    fs::read_to_string(path).unwrap_or_else(|_| panic!("Unable to read {:?}.", path))
+}
+
+fn get_corpus(base_path: &str, files: &[&str]) -> Corpus {
+    let base_path = Path::new(base_path);
+    Corpus {
+        trn: read_text(&base_path.join(files[0])),
+        val: read_text(&base_path.join(files[1])),
+        tst: read_text(&base_path.join(files[2])),
+    }
+}
+
+/// The works of Shakespeare as a single 1.1MB text. The first 90% of the characters form the
+/// training split and the rest the validation split, the test split is empty.
+pub mod tiny_shakespeare {
+    pub use super::Corpus;
+    use std::path::Path;
+
+    static BASE_PATH: &str = "data/tiny_shakespeare/";
+    static FILENAME: &str = "input.txt";
+
+    pub fn new() -> Corpus {
+        let text = super::read_text(&Path::new(BASE_PATH).join(FILENAME));
+        let split = text
+            .char_indices()
+            .nth(text.chars().count() * 9 / 10)
+            .map_or(text.len(), |(i, _)| i);
+        Corpus {
+            trn: text[..split].to_string(),
+            val: text[split..].to_string(),
+            tst: String::new(),
+        }
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download() {
+        super::download::download_tiny_shakespeare(BASE_PATH, FILENAME).unwrap();
+    }
+}
+
+/// WikiText-2, tokenized Wikipedia articles with rare words already replaced by `<unk>`.
+pub mod wikitext2 {
+    pub use super::Corpus;
+
+    static BASE_PATH: &str = "data/wikitext-2/";
+    static FILES: &[&str] = &["wiki.train.tokens", "wiki.valid.tokens", "wiki.test.tokens"];
+
+    pub fn new() -> Corpus {
+        super::get_corpus(BASE_PATH, FILES)
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download_and_extract() {
+        super::download::download_and_extract_wikitext2(BASE_PATH).unwrap();
+    }
+}
+
+/// The Penn Treebank corpus as preprocessed by Mikolov et al. with a vocabulary of 10,000 words.
+pub mod ptb {
+    pub use super::Corpus;
+
+    static BASE_PATH: &str = "data/ptb/";
+    static FILES: &[&str] = &["ptb.train.txt", "ptb.valid.txt", "ptb.test.txt"];
+
+    pub fn new() -> Corpus {
+        super::get_corpus(BASE_PATH, FILES)
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download() {
+        super::download::download_ptb(BASE_PATH, FILES).unwrap();
+    }
+}
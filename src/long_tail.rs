@@ -0,0 +1,84 @@
+//! Long-tailed variants of balanced datasets, e.g. CIFAR-10-LT and CIFAR-100-LT.
+//!
+//! Following the protocol of Cui et al. (2019) and Cao et al. (2019), the training set keeps
+//! `n_max` samples of class 0 and fewer of every later class, so that the largest and the
+//! smallest class differ by `ratio`. `n_max` is the size of the smallest training class, which
+//! is `n / classes` for balanced datasets like CIFAR and keeps every class within its
+//! available samples for the nearly balanced MNIST. The test set stays balanced.
+//! `dataset::Dataset::long_tailed` draws the samples.
+//!
+//! ```no_run
+//! use datasets::cifar10;
+//! use datasets::dataset::Dataset;
+//! use datasets::long_tail::Imbalance;
+//!
+//! let data = cifar10::new_normalized();
+//! let (lt, counts) = data.long_tailed(Imbalance::Exponential, 100., 0);
+//! assert_eq!(counts[0], 5000);
+//! assert_eq!(counts[9], 50);
+//! assert_eq!(lt.trn_img.shape()[0], counts.iter().sum::<usize>());
+//! ```
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Imbalance {
+    /// Class `i` of `C` keeps `n_max * (1 / ratio)^(i / (C - 1))` samples.
+    Exponential,
+    /// The first `C / 2` classes keep `n_max` samples, the others `n_max / ratio`.
+    Step,
+}
+
+/// Number of samples of every class, truncated to integers like the reference implementation.
+pub fn counts(n_max: usize, classes: usize, imbalance: Imbalance, ratio: f64) -> Vec<usize> {
+    assert!(
+        ratio >= 1.,
+        "Imbalance ratio has to be at least 1, got {}.",
+        ratio
+    );
+    let factor = 1. / ratio;
+    (0..classes)
+        .map(|i| {
+            let share = match imbalance {
+                Imbalance::Exponential if classes > 1 => {
+                    factor.powf(i as f64 / (classes - 1) as f64)
+                }
+                Imbalance::Exponential => 1.,
+                Imbalance::Step if i < classes / 2 => 1.,
+                Imbalance::Step => factor,
+            };
+            (n_max as f64 * share) as usize
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exponential() {
+        assert_eq!(
+            counts(5000, 10, Imbalance::Exponential, 100.),
+            vec![5000, 2997, 1796, 1077, 645, 387, 232, 139, 83, 50]
+        );
+        let cifar100 = counts(500, 100, Imbalance::Exponential, 100.);
+        assert_eq!((cifar100[0], cifar100[99]), (500, 5));
+        assert!(cifar100.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!(counts(5000, 1, Imbalance::Exponential, 100.), vec![5000]);
+        assert_eq!(counts(50, 3, Imbalance::Exponential, 1.), vec![50; 3]);
+    }
+
+    #[test]
+    fn step() {
+        assert_eq!(
+            counts(5000, 10, Imbalance::Step, 10.),
+            vec![5000, 5000, 5000, 5000, 5000, 500, 500, 500, 500, 500]
+        );
+        assert_eq!(counts(100, 3, Imbalance::Step, 4.), vec![100, 25, 25]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Imbalance ratio has to be at least 1")]
+    fn rejects_ratio_below_one() {
+        counts(100, 10, Imbalance::Exponential, 0.5);
+    }
+}
@@ -16,22 +16,23 @@ pub fn labels(path: &Path, expected_length: u32) -> Vec<u8> {
         .unwrap_or_else(|_| panic!("Unable to read magic number from {:?}.", path));
     assert!(
         LBL_MAGIC_NUMBER == magic_number,
-        format!(
-            "Expected magic number {} got {}.",
-            LBL_MAGIC_NUMBER, magic_number
-        )
+        "Expected magic number {} got {}.",
+        LBL_MAGIC_NUMBER,
+        magic_number
     );
     let length = file
         .read_u32::<BigEndian>()
         .unwrap_or_else(|_| panic!("Unable to length from {:?}.", path));
     assert!(
         expected_length == length,
-        format!(
-            "Expected data set length of {} got {}.",
-            expected_length, length
-        )
+        "Expected data set length of {} got {}.",
+        expected_length,
+        length
     );
-    file.bytes().map(|b| b.unwrap()).collect()
+    let mut content: Vec<u8> = Vec::new();
+    file.read_to_end(&mut content)
+        .unwrap_or_else(|_| panic!("Unable to read labels from {:?}.", path));
+    content
 }
 
 pub fn images(path: &Path, expected_length: u32) -> Vec<u8> {
@@ -53,20 +54,18 @@ pub fn images(path: &Path, expected_length: u32) -> Vec<u8> {
         .unwrap_or_else(|_| panic!("Unable to read magic number from {:?}.", path));
     assert!(
         IMG_MAGIC_NUMBER == magic_number,
-        format!(
-            "Expected magic number {} got {}.",
-            IMG_MAGIC_NUMBER, magic_number
-        )
+        "Expected magic number {} got {}.",
+        IMG_MAGIC_NUMBER,
+        magic_number
     );
     let length = file
         .read_u32::<BigEndian>()
         .unwrap_or_else(|_| panic!("Unable to length from {:?}.", path));
     assert!(
         expected_length == length,
-        format!(
-            "Expected data set length of {} got {}.",
-            expected_length, length
-        )
+        "Expected data set length of {} got {}.",
+        expected_length,
+        length
     );
     let rows = file
         .read_u32::<BigEndian>()
@@ -74,7 +73,9 @@ pub fn images(path: &Path, expected_length: u32) -> Vec<u8> {
         as usize;
     assert!(
         ROWS == rows,
-        format!("Expected rows length of {} got {}.", ROWS, rows)
+        "Expected rows length of {} got {}.",
+        ROWS,
+        rows
     );
     let cols = file
         .read_u32::<BigEndian>()
@@ -82,7 +83,9 @@ pub fn images(path: &Path, expected_length: u32) -> Vec<u8> {
         as usize;
     assert!(
         COLS == cols,
-        format!("Expected cols length of {} got {}.", COLS, cols)
+        "Expected cols length of {} got {}.",
+        COLS,
+        cols
     );
     // Convert `file` from a Vec to a slice.
     file.to_vec()
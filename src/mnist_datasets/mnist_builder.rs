@@ -1,9 +1,13 @@
-use ndarray::{Array2, Array3};
+use ndarray::{concatenate, s, Array, Array2, Array3, Axis, Dimension, Ix3, IxDyn, RemoveAxis};
+use rayon::prelude::*;
 use std::path::Path;
 
 #[cfg(feature = "download")]
 use super::download;
 use super::helper;
+use crate::dataset::{self, Dataset};
+use crate::layout::Layout;
+use crate::transforms::{image_rng, Transform};
 
 static TRN_IMG_FILENAME: &str = "train-images-idx3-ubyte";
 static TRN_LBL_FILENAME: &str = "train-labels-idx1-ubyte";
@@ -15,13 +19,91 @@ static CLASSES: usize = 10;
 static ROWS: usize = 28;
 static COLS: usize = 28;
 
-pub struct Data {
-    pub trn_img: Array3<f32>,
+/// Images in NHW layout unless loaded with `new_with_layout`, which gives `Data<IxDyn>`.
+pub struct Data<D: Dimension = Ix3> {
+    pub trn_img: Array<f32, D>,
     pub trn_lbl: Array2<f32>,
-    pub tst_img: Array3<f32>,
+    pub tst_img: Array<f32, D>,
     pub tst_lbl: Array2<f32>,
 }
 
+impl<D: Dimension + RemoveAxis> Dataset for Data<D> {
+    type Dim = D;
+
+    fn from_parts(
+        trn_img: Array<f32, D>,
+        trn_lbl: Array2<f32>,
+        tst_img: Array<f32, D>,
+        tst_lbl: Array2<f32>,
+    ) -> Data<D> {
+        Data {
+            trn_img,
+            trn_lbl,
+            tst_img,
+            tst_lbl,
+        }
+    }
+
+    fn trn(&self) -> (&Array<f32, D>, &Array2<f32>) {
+        (&self.trn_img, &self.trn_lbl)
+    }
+
+    fn tst(&self) -> (&Array<f32, D>, &Array2<f32>) {
+        (&self.tst_img, &self.tst_lbl)
+    }
+}
+
+impl Data {
+    /// Load a dataset stored by `save_npz`.
+    pub fn load_npz<P: AsRef<Path>>(path: P) -> Result<Data, String> {
+        let (trn_img, trn_lbl, tst_img, tst_lbl) = dataset::read_npz(path)?;
+        Ok(Data {
+            trn_img,
+            trn_lbl,
+            tst_img,
+            tst_lbl,
+        })
+    }
+
+    /// Extend the training set by `copies` augmented copies of every training image, e.g. with
+    /// `transforms::ElasticDistortion`. The originals come first, followed by one copy of the
+    /// whole training set after another. The test set is left unchanged.
+    ///
+    /// Image `i` of copy `k` is transformed with a generator derived from `seed` and
+    /// `k * n + i`, so the result only depends on the seed.
+    pub fn augmented<T: Transform + ?Sized>(
+        &self,
+        transform: &T,
+        copies: usize,
+        seed: u64,
+    ) -> Data {
+        let (n, rows, cols) = self.trn_img.dim();
+        let images: Vec<Array2<f32>> = (0..copies * n)
+            .into_par_iter()
+            .map(|index| {
+                let image = self.trn_img.index_axis(Axis(0), index % n);
+                let image = image.to_owned().insert_axis(Axis(0));
+                transform
+                    .apply(image, &mut image_rng(seed, index))
+                    .into_shape((rows, cols))
+                    .expect("Transforms must not change the image shape.")
+            })
+            .collect();
+        let mut trn_img = Array3::zeros((n * (copies + 1), rows, cols));
+        trn_img.slice_mut(s![..n, .., ..]).assign(&self.trn_img);
+        for (mut target, image) in trn_img.outer_iter_mut().skip(n).zip(images) {
+            target.assign(&image);
+        }
+        let labels = vec![self.trn_lbl.view(); copies + 1];
+        Data {
+            trn_img,
+            trn_lbl: concatenate(Axis(0), &labels).unwrap(),
+            tst_img: self.tst_img.clone(),
+            tst_lbl: self.tst_lbl.clone(),
+        }
+    }
+}
+
 pub fn get_data(base_path: &str) -> Data {
     let trn_img_filename = TRN_IMG_FILENAME;
     let trn_lbl_filename = TRN_LBL_FILENAME;
@@ -34,10 +116,9 @@ pub fn get_data(base_path: &str) -> Data {
     let available_length = (TRN_LEN + TST_LEN) as usize;
     assert!(
         total_length <= available_length,
-        format!(
-            "Total data set length ({}) greater than maximum possible length ({}).",
-            total_length, available_length
-        )
+        "Total data set length ({}) greater than maximum possible length ({}).",
+        total_length,
+        available_length
     );
     let trn_img = helper::images(&Path::new(base_path).join(trn_img_filename), TRN_LEN);
     let mut trn_lbl = helper::labels(&Path::new(base_path).join(trn_lbl_filename), TRN_LEN);
@@ -46,12 +127,11 @@ pub fn get_data(base_path: &str) -> Data {
     if one_hot {
         fn digit2one_hot(v: Vec<u8>) -> Vec<u8> {
             v.iter()
-                .map(|&i| {
-                    let mut v = vec![0; CLASSES as usize];
+                .flat_map(|&i| {
+                    let mut v = vec![0; CLASSES];
                     v[i as usize] = 1;
                     v
                 })
-                .flatten()
                 .collect()
         }
         trn_lbl = digit2one_hot(trn_lbl);
@@ -94,8 +174,20 @@ pub fn get_normalized_data(base_path: &str) -> Data {
     }
 }
 
+/// Arrange the NHW images of `data` in `layout` without copying them.
+pub fn with_layout(data: Data, layout: Layout) -> Data<IxDyn> {
+    Data {
+        trn_img: layout.apply_nhw(data.trn_img),
+        trn_lbl: data.trn_lbl,
+        tst_img: layout.apply_nhw(data.tst_img),
+        tst_lbl: data.tst_lbl,
+    }
+}
+
 pub mod mnist {
     pub use super::Data;
+    use crate::layout::Layout;
+    use ndarray::IxDyn;
     static BASE_PATH: &str = "data/mnist";
     pub fn new() -> Data {
         super::get_data(BASE_PATH)
@@ -103,6 +195,11 @@ pub mod mnist {
     pub fn new_normalized() -> Data {
         super::get_normalized_data(BASE_PATH)
     }
+    /// Images in `layout` instead of NHW, see `layout::Layout`.
+    pub fn new_with_layout(layout: Layout, normalized: bool) -> Data<IxDyn> {
+        let data = if normalized { new_normalized() } else { new() };
+        super::with_layout(data, layout)
+    }
 
     #[cfg(feature = "download")]
     pub fn download_and_extract() {
@@ -113,6 +210,8 @@ pub mod mnist {
 
 pub mod mnist_fashion {
     pub use super::Data;
+    use crate::layout::Layout;
+    use ndarray::IxDyn;
     static BASE_PATH_FASHION: &str = "data/mnist_fashion";
     pub fn new() -> Data {
         super::get_data(BASE_PATH_FASHION)
@@ -120,6 +219,11 @@ pub mod mnist_fashion {
     pub fn new_normalized() -> Data {
         super::get_normalized_data(BASE_PATH_FASHION)
     }
+    /// Images in `layout` instead of NHW, see `layout::Layout`.
+    pub fn new_with_layout(layout: Layout, normalized: bool) -> Data<IxDyn> {
+        let data = if normalized { new_normalized() } else { new() };
+        super::with_layout(data, layout)
+    }
     #[cfg(feature = "download")]
     pub fn download_and_extract() {
         super::download::download_and_extract(BASE_PATH_FASHION, true).unwrap();
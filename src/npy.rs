@@ -0,0 +1,660 @@
+//! Reading and writing NumPy `.npy` files and `.npz` archives.
+//!
+//! Typed reads require the stored dtype to match the requested element type, e.g. a `uint8`
+//! array can be read as `u8` but not as `f32`. `NdArray` keeps the raw contents for callers
+//! that need to convert between types.
+
+use ndarray::{ArrayBase, ArrayD, Data, Dimension, IxDyn};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::Path;
+
+static MAGIC: &[u8] = b"\x93NUMPY";
+static ALIGNMENT: usize = 64;
+static WRITE_CHUNK: usize = 1 << 16;
+
+/// Element type of a numpy array, e.g. `<f4` for little endian `float32`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DType {
+    pub kind: char,
+    pub size: usize,
+    pub big_endian: bool,
+}
+
+impl DType {
+    /// Parse a numpy type string such as `<f4`, `|u1` or `b1`.
+    pub fn parse(descr: &str) -> Result<DType, String> {
+        let (big_endian, rest) = match descr.chars().next() {
+            Some('>') => (true, &descr[1..]),
+            Some('<') | Some('|') | Some('=') => (false, &descr[1..]),
+            _ => (false, descr),
+        };
+        let mut chars = rest.chars();
+        let kind = chars.next().ok_or("Empty numpy dtype")?;
+        let size = chars
+            .as_str()
+            .parse::<usize>()
+            .map_err(|_| format!("Unsupported numpy dtype {:?}", descr))?;
+        match (kind, size) {
+            ('b', 1)
+            | ('u', 1)
+            | ('u', 2)
+            | ('u', 4)
+            | ('u', 8)
+            | ('i', 1)
+            | ('i', 2)
+            | ('i', 4)
+            | ('i', 8)
+            | ('f', 4)
+            | ('f', 8) => Ok(DType {
+                kind,
+                size,
+                big_endian,
+            }),
+            _ => Err(format!("Unsupported numpy dtype {:?}", descr)),
+        }
+    }
+
+    fn descr(&self) -> String {
+        let byte_order = if self.size == 1 {
+            '|'
+        } else if self.big_endian {
+            '>'
+        } else {
+            '<'
+        };
+        format!("{}{}{}", byte_order, self.kind, self.size)
+    }
+}
+
+/// Element types which can be stored in `.npy` files.
+pub trait Element: Copy {
+    /// Kind of the matching numpy dtype, `b`ool, `u`nsigned, `i`nteger or `f`loat.
+    const KIND: char;
+    const SIZE: usize;
+    fn from_bytes(bytes: &[u8], big_endian: bool) -> Self;
+    fn extend_le_bytes(self, out: &mut Vec<u8>);
+}
+
+macro_rules! impl_element {
+    ($t:ty, $kind:expr) => {
+        impl Element for $t {
+            const KIND: char = $kind;
+            const SIZE: usize = std::mem::size_of::<$t>();
+            fn from_bytes(bytes: &[u8], big_endian: bool) -> $t {
+                let mut array = [0; std::mem::size_of::<$t>()];
+                array.copy_from_slice(bytes);
+                if big_endian {
+                    <$t>::from_be_bytes(array)
+                } else {
+                    <$t>::from_le_bytes(array)
+                }
+            }
+            fn extend_le_bytes(self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+        }
+    };
+}
+
+impl_element!(u8, 'u');
+impl_element!(u16, 'u');
+impl_element!(u32, 'u');
+impl_element!(u64, 'u');
+impl_element!(i8, 'i');
+impl_element!(i16, 'i');
+impl_element!(i32, 'i');
+impl_element!(i64, 'i');
+impl_element!(f32, 'f');
+impl_element!(f64, 'f');
+
+impl Element for bool {
+    const KIND: char = 'b';
+    const SIZE: usize = 1;
+    fn from_bytes(bytes: &[u8], _big_endian: bool) -> bool {
+        bytes[0] != 0
+    }
+    fn extend_le_bytes(self, out: &mut Vec<u8>) {
+        out.push(self as u8);
+    }
+}
+
+/// Raw contents of a numpy array.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NdArray {
+    pub shape: Vec<usize>,
+    pub dtype: DType,
+    pub fortran_order: bool,
+    pub data: Vec<u8>,
+}
+
+impl NdArray {
+    /// The array as `T`, which requires the dtype to match `T` exactly.
+    pub fn to_array<T: Element>(&self) -> Result<ArrayD<T>, String> {
+        if self.dtype.kind != T::KIND || self.dtype.size != T::SIZE {
+            return Err(format!(
+                "Expected an array of dtype {}{}, got {:?}",
+                T::KIND,
+                T::SIZE,
+                self.dtype
+            ));
+        }
+        let values = self
+            .data
+            .chunks(T::SIZE)
+            .map(|b| T::from_bytes(b, self.dtype.big_endian))
+            .collect();
+        self.shaped(values)
+    }
+
+    /// The array as `u8`, which requires a `uint8` or `bool` dtype.
+    pub fn to_u8(&self) -> Result<ArrayD<u8>, String> {
+        if self.dtype.size != 1 || self.dtype.kind == 'i' {
+            return Err(format!("Expected a uint8 array, got {:?}", self.dtype));
+        }
+        self.shaped(self.data.clone())
+    }
+
+    /// The array as `i64`, which requires an integer or `bool` dtype.
+    pub fn to_i64(&self) -> Result<ArrayD<i64>, String> {
+        if self.dtype.kind == 'f' {
+            return Err(format!("Expected an integer array, got {:?}", self.dtype));
+        }
+        let values = self
+            .data
+            .chunks(self.dtype.size)
+            .map(|b| self.int(b))
+            .collect();
+        self.shaped(values)
+    }
+
+    /// The array as `f64`, converting from any supported dtype.
+    pub fn to_f64(&self) -> Result<ArrayD<f64>, String> {
+        let values = self
+            .data
+            .chunks(self.dtype.size)
+            .map(|b| match self.dtype.kind {
+                'f' if self.dtype.size == 4 => f32::from_bits(self.int(b) as u32) as f64,
+                'f' => f64::from_bits(self.int(b) as u64),
+                _ => self.int(b) as f64,
+            })
+            .collect();
+        self.shaped(values)
+    }
+
+    /// Decode a single integer element, sign extending signed types.
+    fn int(&self, bytes: &[u8]) -> i64 {
+        let mut value: u64 = 0;
+        for i in 0..bytes.len() {
+            let byte = if self.dtype.big_endian {
+                bytes[i]
+            } else {
+                bytes[bytes.len() - 1 - i]
+            };
+            value = (value << 8) | byte as u64;
+        }
+        let bits = 8 * bytes.len() as u32;
+        if self.dtype.kind == 'i' && bits < 64 {
+            let shift = 64 - bits;
+            ((value << shift) as i64) >> shift
+        } else {
+            value as i64
+        }
+    }
+
+    fn shaped<T>(&self, values: Vec<T>) -> Result<ArrayD<T>, String> {
+        let array = if self.fortran_order {
+            let mut reversed = self.shape.clone();
+            reversed.reverse();
+            ArrayD::from_shape_vec(IxDyn(&reversed), values).map(|a| a.reversed_axes())
+        } else {
+            ArrayD::from_shape_vec(IxDyn(&self.shape), values)
+        };
+        array.map_err(|e| format!("Failed to shape numpy array: {:?}", e))
+    }
+}
+
+/// Parse the raw contents of a `.npy` file.
+pub fn read_raw<R: Read>(mut reader: R) -> Result<NdArray, String> {
+    let mut magic = [0; 8];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|e| format!("Failed to read npy header: {:?}", e))?;
+    if &magic[..6] != MAGIC {
+        return Err("Not a npy file".to_string());
+    }
+    let header_len = match magic[6] {
+        1 => {
+            let mut len = [0; 2];
+            reader
+                .read_exact(&mut len)
+                .map_err(|e| format!("Failed to read npy header: {:?}", e))?;
+            u16::from_le_bytes(len) as usize
+        }
+        2 | 3 => {
+            let mut len = [0; 4];
+            reader
+                .read_exact(&mut len)
+                .map_err(|e| format!("Failed to read npy header: {:?}", e))?;
+            u32::from_le_bytes(len) as usize
+        }
+        version => return Err(format!("Unsupported npy version {}", version)),
+    };
+    let mut header = Vec::new();
+    reader
+        .by_ref()
+        .take(header_len as u64)
+        .read_to_end(&mut header)
+        .map_err(|e| format!("Failed to read npy header: {:?}", e))?;
+    if header.len() != header_len {
+        return Err("Unexpected end of npy header".to_string());
+    }
+    let header = String::from_utf8_lossy(&header);
+
+    let descr = header_value(&header, "descr")?;
+    let dtype = DType::parse(descr.trim_matches(|c| c == '\'' || c == '"'))?;
+    let fortran_order = match header_value(&header, "fortran_order")? {
+        "True" => true,
+        "False" => false,
+        other => return Err(format!("Invalid fortran_order {:?}", other)),
+    };
+    let shape = header_value(&header, "shape")?
+        .trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .map(|d| d.trim())
+        .filter(|d| !d.is_empty())
+        .map(|d| {
+            d.parse::<usize>()
+                .map_err(|_| format!("Invalid shape {:?}", header))
+        })
+        .collect::<Result<Vec<usize>, String>>()?;
+
+    let size = shape
+        .iter()
+        .try_fold(dtype.size, |size, &d| size.checked_mul(d))
+        .ok_or_else(|| format!("Numpy array of shape {:?} is too large", shape))?;
+    // The data is read without preallocating, as the header can claim any shape.
+    let mut data = Vec::new();
+    reader
+        .take(size as u64)
+        .read_to_end(&mut data)
+        .map_err(|e| format!("Failed to read npy data: {:?}", e))?;
+    if data.len() != size {
+        return Err(format!("Npy data holds {} of {} bytes", data.len(), size));
+    }
+    Ok(NdArray {
+        shape,
+        dtype,
+        fortran_order,
+        data,
+    })
+}
+
+/// Value of `key` in the python dict literal of a npy header.
+fn header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, String> {
+    let missing = || format!("No {} in npy header {:?}", key, header);
+    let start = header.find(&format!("'{}'", key)).ok_or_else(missing)? + key.len() + 2;
+    let rest = header[start..]
+        .trim_start()
+        .strip_prefix(':')
+        .ok_or_else(missing)?;
+    let rest = rest.trim_start();
+    let end = if rest.starts_with('(') {
+        rest.find(')').map(|i| i + 1)
+    } else {
+        rest.find(&[',', '}'][..])
+    };
+    Ok(rest[..end.ok_or_else(missing)?].trim())
+}
+
+pub fn read_npy<T: Element, R: Read>(reader: R) -> Result<ArrayD<T>, String> {
+    read_raw(reader)?.to_array()
+}
+
+pub fn write_npy<T, S, D, W>(mut writer: W, array: &ArrayBase<S, D>) -> Result<(), String>
+where
+    T: Element,
+    S: Data<Elem = T>,
+    D: Dimension,
+    W: Write,
+{
+    let dtype = DType {
+        kind: T::KIND,
+        size: T::SIZE,
+        big_endian: false,
+    };
+    let shape = match array.shape() {
+        [d] => format!("({},)", d),
+        shape => format!(
+            "({})",
+            shape
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        dtype.descr(),
+        shape
+    );
+    // Pad with spaces so the data starts aligned, the header ends with a newline.
+    let prefix_len = if header.len() + 11 > u16::MAX as usize {
+        12
+    } else {
+        10
+    };
+    let padding = ALIGNMENT - (prefix_len + header.len() + 1) % ALIGNMENT;
+    header.push_str(&" ".repeat(padding % ALIGNMENT));
+    header.push('\n');
+
+    let mut prefix: Vec<u8> = Vec::with_capacity(prefix_len + header.len());
+    prefix.extend_from_slice(MAGIC);
+    if prefix_len == 10 {
+        prefix.extend_from_slice(&[1, 0]);
+        prefix.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    } else {
+        prefix.extend_from_slice(&[2, 0]);
+        prefix.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    }
+    prefix.extend_from_slice(header.as_bytes());
+    let write_error = |e| format!("Failed to write npy data: {:?}", e);
+    writer.write_all(&prefix).map_err(write_error)?;
+
+    // Iterating in logical order writes C order independent of the memory layout. The elements
+    // are written in chunks to keep memory usage low for large arrays.
+    let mut chunk: Vec<u8> = Vec::with_capacity(WRITE_CHUNK + T::SIZE);
+    for &x in array.iter() {
+        x.extend_le_bytes(&mut chunk);
+        if chunk.len() >= WRITE_CHUNK {
+            writer.write_all(&chunk).map_err(write_error)?;
+            chunk.clear();
+        }
+    }
+    writer.write_all(&chunk).map_err(write_error)
+}
+
+pub fn load_npy<T: Element, P: AsRef<Path>>(path: P) -> Result<ArrayD<T>, String> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|e| format!("Failed to open {:?}: {:?}", path, e))?;
+    read_npy(BufReader::new(file)).map_err(|e| format!("Failed to read {:?}: {}", path, e))
+}
+
+pub fn save_npy<T, S, D, P>(path: P, array: &ArrayBase<S, D>) -> Result<(), String>
+where
+    T: Element,
+    S: Data<Elem = T>,
+    D: Dimension,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let file = File::create(path).map_err(|e| format!("Failed to create {:?}: {:?}", path, e))?;
+    write_npy(BufWriter::new(file), array)
+}
+
+/// Reads the arrays of a `.npz` archive, stored or deflated.
+pub struct NpzReader<R: Read + Seek> {
+    zip: ZipArchive<R>,
+}
+
+impl NpzReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<NpzReader<BufReader<File>>, String> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| format!("Failed to open {:?}: {:?}", path, e))?;
+        NpzReader::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read + Seek> NpzReader<R> {
+    pub fn new(reader: R) -> Result<NpzReader<R>, String> {
+        let zip = ZipArchive::new(reader).map_err(|e| format!("Failed to read npz: {:?}", e))?;
+        Ok(NpzReader { zip })
+    }
+
+    /// Names of the stored arrays, without the `.npy` suffix.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .zip
+            .file_names()
+            .map(|name| name.trim_end_matches(".npy").to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn by_name_raw(&mut self, name: &str) -> Result<NdArray, String> {
+        let file_name = if name.ends_with(".npy") {
+            name.to_string()
+        } else {
+            format!("{}.npy", name)
+        };
+        let entry = self
+            .zip
+            .by_name(&file_name)
+            .map_err(|e| format!("Failed to read {} from npz: {:?}", name, e))?;
+        read_raw(entry).map_err(|e| format!("Failed to read {} from npz: {}", name, e))
+    }
+
+    pub fn by_name<T: Element>(&mut self, name: &str) -> Result<ArrayD<T>, String> {
+        self.by_name_raw(name)?
+            .to_array()
+            .map_err(|e| format!("Failed to read {} from npz: {}", name, e))
+    }
+}
+
+/// Writes arrays into a `.npz` archive, like `numpy.savez` or `numpy.savez_compressed`.
+pub struct NpzWriter<W: Write + Seek> {
+    zip: ZipWriter<W>,
+    options: FileOptions,
+}
+
+impl NpzWriter<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        compressed: bool,
+    ) -> Result<NpzWriter<BufWriter<File>>, String> {
+        let path = path.as_ref();
+        let file =
+            File::create(path).map_err(|e| format!("Failed to create {:?}: {:?}", path, e))?;
+        let writer = BufWriter::new(file);
+        Ok(if compressed {
+            NpzWriter::new_compressed(writer)
+        } else {
+            NpzWriter::new(writer)
+        })
+    }
+}
+
+impl<W: Write + Seek> NpzWriter<W> {
+    pub fn new(writer: W) -> NpzWriter<W> {
+        NpzWriter {
+            zip: ZipWriter::new(writer),
+            options: FileOptions::default()
+                .compression_method(CompressionMethod::Stored)
+                .large_file(true),
+        }
+    }
+
+    pub fn new_compressed(writer: W) -> NpzWriter<W> {
+        NpzWriter {
+            zip: ZipWriter::new(writer),
+            options: FileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .large_file(true),
+        }
+    }
+
+    pub fn add_array<T, S, D>(&mut self, name: &str, array: &ArrayBase<S, D>) -> Result<(), String>
+    where
+        T: Element,
+        S: Data<Elem = T>,
+        D: Dimension,
+    {
+        self.zip
+            .start_file(format!("{}.npy", name), self.options)
+            .map_err(|e| format!("Failed to add {} to npz: {:?}", name, e))?;
+        write_npy(&mut self.zip, array)
+    }
+
+    pub fn finish(mut self) -> Result<W, String> {
+        self.zip
+            .finish()
+            .map_err(|e| format!("Failed to finish npz: {:?}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{arr2, Array, Array2, Array3, ShapeBuilder};
+    use std::io::Cursor;
+
+    /// A version 1.0 `.npy` file with the given header dict and raw data.
+    fn npy_file(header: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16 + 1).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        bytes.push(b'\n');
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn round_trip<T: Element, D: Dimension>(array: &Array<T, D>) -> ArrayD<T> {
+        let mut bytes = Vec::new();
+        write_npy(&mut bytes, array).unwrap();
+        let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+        assert_eq!((10 + header_len) % ALIGNMENT, 0);
+        read_npy(bytes.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn round_trip_dtypes() {
+        let floats = Array3::from_shape_fn((2, 3, 4), |(i, j, k)| (i * 12 + j * 4 + k) as f32 / 3.);
+        assert_eq!(round_trip(&floats), floats.clone().into_dyn());
+        let bytes = arr2(&[[0u8, 1, 255], [7, 8, 9]]);
+        assert_eq!(round_trip(&bytes), bytes.into_dyn());
+        let ints = Array::from(vec![i64::MIN, -1, 0, i64::MAX]);
+        assert_eq!(round_trip(&ints), ints.into_dyn());
+        let flags = Array::from(vec![true, false, true]);
+        assert_eq!(round_trip(&flags), flags.into_dyn());
+        let empty = Array2::<f64>::zeros((0, 5));
+        assert_eq!(round_trip(&empty), empty.into_dyn());
+    }
+
+    #[test]
+    fn round_trip_larger_than_a_chunk() {
+        let n = WRITE_CHUNK / 4 * 3 + 5;
+        let values = Array::from_shape_fn(n, |i| i as u32);
+        assert_eq!(round_trip(&values), values.into_dyn());
+    }
+
+    #[test]
+    fn writes_fortran_order_input_as_c_order() {
+        let fortran = Array2::from_shape_vec((2, 3).f(), vec![1., 4., 2., 5., 3., 6.]).unwrap();
+        let read = round_trip(&fortran);
+        assert_eq!(read, arr2(&[[1f64, 2., 3.], [4., 5., 6.]]).into_dyn());
+        assert!(read.is_standard_layout());
+    }
+
+    #[test]
+    fn reads_fortran_order() {
+        let data: Vec<u8> = [1i16, 4, 2, 5, 3, 6]
+            .iter()
+            .flat_map(|x| x.to_le_bytes().to_vec())
+            .collect();
+        let file = npy_file(
+            "{'descr': '<i2', 'fortran_order': True, 'shape': (2, 3), }",
+            &data,
+        );
+        let array: ArrayD<i16> = read_npy(file.as_slice()).unwrap();
+        assert_eq!(array, arr2(&[[1i16, 2, 3], [4, 5, 6]]).into_dyn());
+    }
+
+    #[test]
+    fn reads_big_endian() {
+        assert_eq!(
+            DType::parse(">f8"),
+            Ok(DType {
+                kind: 'f',
+                size: 8,
+                big_endian: true
+            })
+        );
+        assert_eq!(DType::parse("|u1").unwrap().descr(), "|u1");
+        assert!(DType::parse("<c16").is_err());
+
+        let data: Vec<u8> = [1i32, -2, 70000]
+            .iter()
+            .flat_map(|x| x.to_be_bytes().to_vec())
+            .collect();
+        let file = npy_file(
+            "{'descr': '>i4', 'fortran_order': False, 'shape': (3,), }",
+            &data,
+        );
+        let raw = read_raw(file.as_slice()).unwrap();
+        assert_eq!(
+            raw.to_array::<i32>().unwrap().into_raw_vec(),
+            vec![1, -2, 70000]
+        );
+        assert_eq!(raw.to_i64().unwrap().into_raw_vec(), vec![1, -2, 70000]);
+        assert_eq!(raw.to_f64().unwrap().into_raw_vec(), vec![1., -2., 70000.]);
+        assert!(raw.to_array::<u32>().is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let file = npy_file(
+            "{'descr': '<f4', 'fortran_order': False, 'shape': (3,), }",
+            &[0; 11],
+        );
+        assert!(read_npy::<f32, _>(file.as_slice()).is_err());
+        assert!(read_npy::<f32, _>(&b"\x93NUMPY"[..]).is_err());
+    }
+
+    #[test]
+    fn rejects_huge_shapes() {
+        for shape in &["(100000000000,)", "(4294967296, 4294967296)", "(-1,)"] {
+            let header = format!(
+                "{{'descr': '<f8', 'fortran_order': False, 'shape': {}, }}",
+                shape
+            );
+            let file = npy_file(&header, &[0; 16]);
+            assert!(read_raw(file.as_slice()).is_err(), "shape {}", shape);
+        }
+    }
+
+    #[test]
+    fn npz_round_trip() {
+        let images = Array3::from_shape_fn((3, 2, 2), |(i, j, k)| (i + j * k) as f32);
+        let labels = arr2(&[[1u8, 0], [0, 1], [1, 0]]);
+        for &compressed in &[false, true] {
+            let cursor = Cursor::new(Vec::new());
+            let mut npz = if compressed {
+                NpzWriter::new_compressed(cursor)
+            } else {
+                NpzWriter::new(cursor)
+            };
+            npz.add_array("images", &images).unwrap();
+            npz.add_array("labels", &labels.t()).unwrap();
+            let cursor = npz.finish().unwrap();
+
+            let mut npz = NpzReader::new(Cursor::new(cursor.into_inner())).unwrap();
+            assert_eq!(npz.names(), vec!["images", "labels"]);
+            assert_eq!(
+                npz.by_name::<f32>("images").unwrap(),
+                images.clone().into_dyn()
+            );
+            assert_eq!(
+                npz.by_name::<u8>("labels.npy").unwrap(),
+                labels.t().to_owned().into_dyn()
+            );
+            assert!(npz.by_name::<f64>("images").is_err());
+            assert!(npz.by_name::<f32>("missing").is_err());
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use std::path::Path;
+
+use crate::download_helper::downloader;
+
+const BASE_URL: &str = "https://raw.githubusercontent.com/brendenlake/omniglot/master/python";
+const ARCHIVES_TO_DOWNLOAD: &[&str] = &["images_background.zip", "images_evaluation.zip"];
+
+pub fn download_and_extract(base_path: &str) -> Result<(), String> {
+    println!("Attempting to download and extract Omniglot...");
+    downloader::download(
+        base_path,
+        BASE_URL.to_string(),
+        ARCHIVES_TO_DOWNLOAD.to_vec(),
+    )?;
+    let base_dir = Path::new(base_path);
+    for archive in ARCHIVES_TO_DOWNLOAD {
+        let extract_to = base_dir.join(archive.replace(".zip", ""));
+        if extract_to.exists() {
+            println!(
+                "  Extracted folder {:?} already exists, skipping extraction.",
+                extract_to
+            );
+            continue;
+        }
+        downloader::extract_zip(&base_dir.join(archive), base_dir)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,5 @@
+pub mod omniglot_builder;
+pub use omniglot_builder::omniglot;
+
+#[cfg(feature = "download")]
+mod download;
@@ -0,0 +1,98 @@
+use ndarray::prelude::*;
+use ndarray::{Array1, Array3};
+
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "download")]
+use super::download;
+use crate::image_folder::{self, ImageFolder};
+
+static BACKGROUND_FOLDER: &str = "images_background";
+static EVALUATION_FOLDER: &str = "images_evaluation";
+
+/// One split of Omniglot, laid out as `<alphabet>/<character>/<drawing>.png`.
+///
+/// Images are grayscale `(n, rows, cols)` arrays with white background, 105 x 105 unless resized.
+/// As there are hundreds of characters, labels are class indices instead of one-hot vectors:
+/// `character[i]` indexes `character_names` (`"<alphabet>/<character>"`) and `alphabet[i]`
+/// indexes `alphabet_names`.
+pub struct Split {
+    pub img: Array3<f32>,
+    pub character: Array1<usize>,
+    pub alphabet: Array1<usize>,
+    pub character_names: Vec<String>,
+    pub alphabet_names: Vec<String>,
+}
+
+/// The background split (30 alphabets) used for training and the evaluation split
+/// (20 alphabets) used for testing few-shot learners.
+pub struct Data {
+    pub background: Split,
+    pub evaluation: Split,
+}
+
+fn get_split(
+    split_path: &Path,
+    size: Option<(usize, usize)>,
+    normalized: bool,
+) -> Result<Split, String> {
+    let alphabet_names = ImageFolder::new(split_path).classes()?;
+    let mut character_names: Vec<String> = Vec::new();
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut character: Vec<usize> = Vec::new();
+    let mut alphabet: Vec<usize> = Vec::new();
+    for (a, alphabet_name) in alphabet_names.iter().enumerate() {
+        let alphabet_path = split_path.join(alphabet_name);
+        for character_name in ImageFolder::new(&alphabet_path).classes()? {
+            for path in image_folder::image_paths(&alphabet_path.join(&character_name))? {
+                paths.push(path);
+                character.push(character_names.len());
+                alphabet.push(a);
+            }
+            character_names.push(format!("{}/{}", alphabet_name, character_name));
+        }
+    }
+
+    let mut loader = ImageFolder::new(split_path)
+        .grayscale(true)
+        .normalized(normalized);
+    if let Some((rows, cols)) = size {
+        loader = loader.resize(rows, cols);
+    }
+    let img = loader.decode(&paths)?.index_axis_move(Axis(1), 0);
+    Ok(Split {
+        img,
+        character: Array1::from(character),
+        alphabet: Array1::from(alphabet),
+        character_names,
+        alphabet_names,
+    })
+}
+
+fn get_data(base_path: &str, size: Option<(usize, usize)>, normalized: bool) -> Data {
+    let base_path = Path::new(base_path);
+    Data {
+        background: get_split(&base_path.join(BACKGROUND_FOLDER), size, normalized).unwrap(),
+        evaluation: get_split(&base_path.join(EVALUATION_FOLDER), size, normalized).unwrap(),
+    }
+}
+
+pub mod omniglot {
+    pub use super::{Data, Split};
+    static BASE_PATH: &str = "data/omniglot/";
+    pub fn new() -> Data {
+        super::get_data(BASE_PATH, None, false)
+    }
+    pub fn new_normalized() -> Data {
+        super::get_data(BASE_PATH, None, true)
+    }
+    /// Resize all images, few-shot benchmarks commonly use 28 x 28.
+    pub fn new_resized(rows: usize, cols: usize, normalized: bool) -> Data {
+        super::get_data(BASE_PATH, Some((rows, cols)), normalized)
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download_and_extract() {
+        super::download::download_and_extract(BASE_PATH).unwrap();
+    }
+}
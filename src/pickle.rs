@@ -0,0 +1,943 @@
+//! A minimal reader for Python pickles, sufficient for the dicts of numpy arrays and lists used
+//! by the CIFAR python batches, Downsampled ImageNet and the Planetoid graph files.
+//!
+//! Python 2 `str` objects are returned as `Value::Bytes`, like Python 3 does with
+//! `pickle.load(f, encoding="bytes")`. Objects of arbitrary classes are not instantiated but
+//! returned as `Value::Object`, keeping the callable, its arguments and the state they were
+//! pickled with.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+
+use crate::npy::{DType, NdArray};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    None,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Bytes(Vec<u8>),
+    String(String),
+    List(Vec<Value>),
+    Tuple(Vec<Value>),
+    Dict(Vec<(Value, Value)>),
+    Global {
+        module: String,
+        name: String,
+    },
+    /// The result of calling `callable` with `args`, e.g. a class instance or a numpy array.
+    ///
+    /// `state` holds the argument of a following `BUILD` opcode (`Value::None` otherwise) and
+    /// `items` the entries added by `SETITEM(S)`/`APPEND(S)`, as used by `defaultdict`.
+    Object {
+        callable: Box<Value>,
+        args: Box<Value>,
+        state: Box<Value>,
+        items: Vec<(Value, Value)>,
+    },
+    /// Reference to a memoized object, only used while parsing.
+    #[doc(hidden)]
+    MemoRef(usize),
+}
+
+impl Value {
+    /// Look up `key` in a dict (or the items of an object), matching both `str` and `bytes` keys.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        let items = match self {
+            Value::Dict(items) | Value::Object { items, .. } => items,
+            _ => return None,
+        };
+        items
+            .iter()
+            .find(|(k, _)| match k {
+                Value::String(s) => s == key,
+                Value::Bytes(b) => b.as_slice() == key.as_bytes(),
+                _ => false,
+            })
+            .map(|(_, v)| v)
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match *self {
+            Value::Int(i) => Some(i),
+            Value::Bool(b) => Some(b as i64),
+            _ => None,
+        }
+    }
+
+    /// Text of a `str` or `bytes` object.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            Value::Bytes(b) => std::str::from_utf8(b).ok(),
+            _ => None,
+        }
+    }
+
+    /// Elements of a list, tuple or set.
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(v) | Value::Tuple(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Integer elements of a list, e.g. the `labels` of a CIFAR batch.
+    pub fn to_int_vec(&self) -> Result<Vec<i64>, String> {
+        match self {
+            Value::Object { .. } => Ok(self.to_ndarray()?.to_i64()?.iter().cloned().collect()),
+            _ => self
+                .as_list()
+                .ok_or_else(|| format!("Expected a list, got {}", self.type_name()))?
+                .iter()
+                .map(|v| {
+                    v.as_int()
+                        .ok_or_else(|| format!("Expected an int, got {}", v.type_name()))
+                })
+                .collect(),
+        }
+    }
+
+    /// Interpret a pickled `numpy.ndarray`.
+    pub fn to_ndarray(&self) -> Result<NdArray, String> {
+        let (callable, state) = match self {
+            Value::Object {
+                callable, state, ..
+            } => (callable, state),
+            _ => return Err(format!("Expected a numpy array, got {}", self.type_name())),
+        };
+        if !callable.is_global("_reconstruct") {
+            return Err(format!("Expected a numpy array, got {:?}", callable));
+        }
+        // State of ndarray.__reduce__: (version, shape, dtype, is_fortran, raw data)
+        let state = state
+            .as_list()
+            .filter(|s| s.len() == 5)
+            .ok_or("Unexpected numpy array state")?;
+        let shape = state[1]
+            .as_list()
+            .ok_or("Unexpected numpy array shape")?
+            .iter()
+            .map(|d| d.as_int().and_then(|d| usize::try_from(d).ok()))
+            .collect::<Option<Vec<usize>>>()
+            .ok_or("Unexpected numpy array shape")?;
+        let dtype = dtype_from_pickle(&state[2])?;
+        let fortran_order = state[3].as_int().ok_or("Unexpected numpy array order")? != 0;
+        let data = match &state[4] {
+            Value::Bytes(b) => b.clone(),
+            Value::String(s) => latin1(s),
+            other => {
+                return Err(format!(
+                    "Unsupported numpy array data {}",
+                    other.type_name()
+                ))
+            }
+        };
+        let size = shape
+            .iter()
+            .try_fold(dtype.size, |size, &d| size.checked_mul(d))
+            .ok_or_else(|| format!("Numpy array of shape {:?} is too large", shape))?;
+        if data.len() != size {
+            return Err(format!(
+                "Numpy array of shape {:?} and dtype {:?} holds {} bytes",
+                shape,
+                dtype,
+                data.len()
+            ));
+        }
+        Ok(NdArray {
+            shape,
+            dtype,
+            fortran_order,
+            data,
+        })
+    }
+
+    fn is_global(&self, global_name: &str) -> bool {
+        match self {
+            Value::Global { name, .. } => name == global_name,
+            _ => false,
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::None => "None",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Bytes(_) => "bytes",
+            Value::String(_) => "str",
+            Value::List(_) => "list",
+            Value::Tuple(_) => "tuple",
+            Value::Dict(_) => "dict",
+            Value::Global { .. } => "global",
+            Value::Object { .. } => "object",
+            Value::MemoRef(_) => "memo reference",
+        }
+    }
+}
+
+/// Interpret a pickled `numpy.dtype`.
+fn dtype_from_pickle(value: &Value) -> Result<DType, String> {
+    let (args, state) = match value {
+        Value::Object { args, state, .. } => (args, state),
+        _ => return Err(format!("Expected a numpy dtype, got {}", value.type_name())),
+    };
+    let descr = args
+        .as_list()
+        .and_then(|a| a.first())
+        .and_then(|d| d.as_str())
+        .ok_or("Unexpected numpy dtype arguments")?;
+    // State of dtype.__reduce__: (version, byte order, ...)
+    let byte_order = state
+        .as_list()
+        .and_then(|s| s.get(1))
+        .and_then(|o| o.as_str())
+        .unwrap_or("|");
+    DType::parse(&format!("{}{}", byte_order, descr))
+}
+
+/// Parse the pickle stored at `path`.
+pub fn load(path: &Path) -> Result<Value, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {:?}: {:?}", path, e))?;
+    from_slice(&bytes).map_err(|e| format!("Failed to unpickle {:?}: {}", path, e))
+}
+
+/// Parse a pickle of protocol 0 to 4 from memory.
+pub fn from_slice(bytes: &[u8]) -> Result<Value, String> {
+    let mut parser = Parser {
+        bytes,
+        pos: 0,
+        stack: Vec::new(),
+        marks: Vec::new(),
+        memo: HashMap::new(),
+        objects: Vec::new(),
+    };
+    let value = parser.run()?;
+    parser.resolve(value, 0)
+}
+
+/// Python `str.encode("latin1")`, which Python 3 uses to pickle `bytes` with protocol 2.
+fn latin1(s: &str) -> Vec<u8> {
+    s.chars().map(|c| c as u32 as u8).collect()
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    stack: Vec<Value>,
+    marks: Vec<usize>,
+    /// Index into `objects` of every memo id. Several ids can share an object.
+    memo: HashMap<u32, usize>,
+    /// Memoized objects, which are never `Value::MemoRef` themselves.
+    objects: Vec<Value>,
+}
+
+impl<'a> Parser<'a> {
+    fn run(&mut self) -> Result<Value, String> {
+        loop {
+            let opcode = self.take(1)?[0];
+            match opcode {
+                // PROTO, FRAME
+                0x80 => {
+                    self.take(1)?;
+                }
+                0x95 => {
+                    self.take(8)?;
+                }
+                b'.' => return self.pop(),
+                b'(' => self.marks.push(self.stack.len()),
+                b'0' => {
+                    self.pop()?;
+                }
+                b'1' => {
+                    self.pop_mark()?;
+                }
+                b'2' => {
+                    let top = self.top()?.clone();
+                    self.stack.push(top);
+                }
+
+                b'N' => self.stack.push(Value::None),
+                0x88 => self.stack.push(Value::Bool(true)),
+                0x89 => self.stack.push(Value::Bool(false)),
+                b'I' => {
+                    let line = self.line()?;
+                    let value = match line.as_str() {
+                        "00" => Value::Bool(false),
+                        "01" => Value::Bool(true),
+                        _ => Value::Int(parse_number(&line)?),
+                    };
+                    self.stack.push(value);
+                }
+                b'L' => {
+                    let line = self.line()?;
+                    let value = parse_number(line.trim_end_matches('L'))?;
+                    self.stack.push(Value::Int(value));
+                }
+                b'J' => {
+                    let value = i32::from_le_bytes(self.array()?);
+                    self.stack.push(Value::Int(value as i64));
+                }
+                b'K' => {
+                    let value = self.take(1)?[0];
+                    self.stack.push(Value::Int(value as i64));
+                }
+                b'M' => {
+                    let value = u16::from_le_bytes(self.array()?);
+                    self.stack.push(Value::Int(value as i64));
+                }
+                0x8a | 0x8b => {
+                    let len = if opcode == 0x8a {
+                        self.take(1)?[0] as usize
+                    } else {
+                        self.len4()?
+                    };
+                    let value = long(self.take(len)?)?;
+                    self.stack.push(Value::Int(value));
+                }
+                b'F' => {
+                    let line = self.line()?;
+                    let value = line
+                        .parse::<f64>()
+                        .map_err(|_| format!("Invalid float {:?}", line))?;
+                    self.stack.push(Value::Float(value));
+                }
+                b'G' => {
+                    let value = f64::from_be_bytes(self.array()?);
+                    self.stack.push(Value::Float(value));
+                }
+
+                b'S' => {
+                    let line = self.line()?;
+                    self.stack.push(Value::Bytes(unquote(&line)?));
+                }
+                b'T' | b'U' | b'B' | b'C' | 0x8e | 0x96 => {
+                    let len = match opcode {
+                        b'U' | b'C' => self.take(1)?[0] as usize,
+                        0x8e | 0x96 => self.len8()?,
+                        _ => self.len4()?,
+                    };
+                    let value = self.take(len)?.to_vec();
+                    self.stack.push(Value::Bytes(value));
+                }
+                b'V' => {
+                    let line = self.line()?;
+                    self.stack.push(Value::String(unescape_unicode(&line)?));
+                }
+                b'X' | 0x8c | 0x8d => {
+                    let len = match opcode {
+                        0x8c => self.take(1)?[0] as usize,
+                        0x8d => self.len8()?,
+                        _ => self.len4()?,
+                    };
+                    let value = String::from_utf8(self.take(len)?.to_vec())
+                        .map_err(|e| format!("Invalid utf-8 string: {:?}", e))?;
+                    self.stack.push(Value::String(value));
+                }
+
+                b']' => self.stack.push(Value::List(Vec::new())),
+                b')' => self.stack.push(Value::Tuple(Vec::new())),
+                b'}' => self.stack.push(Value::Dict(Vec::new())),
+                0x8f => self.stack.push(Value::List(Vec::new())),
+                b'l' | 0x91 => {
+                    let items = self.pop_mark()?;
+                    self.stack.push(Value::List(items));
+                }
+                b't' => {
+                    let items = self.pop_mark()?;
+                    self.stack.push(Value::Tuple(items));
+                }
+                0x85..=0x87 => {
+                    let len = (opcode - 0x84) as usize;
+                    if self.stack.len() < len {
+                        return Err("Stack underflow".to_string());
+                    }
+                    let items = self.stack.split_off(self.stack.len() - len);
+                    self.stack.push(Value::Tuple(items));
+                }
+                b'd' => {
+                    let items = pairs(self.pop_mark()?)?;
+                    self.stack.push(Value::Dict(items));
+                }
+                b'a' => {
+                    let item = self.pop()?;
+                    self.extend(vec![item])?;
+                }
+                b'e' | 0x90 => {
+                    let items = self.pop_mark()?;
+                    self.extend(items)?;
+                }
+                b's' => {
+                    let value = self.pop()?;
+                    let key = self.pop()?;
+                    self.set_items(vec![(key, value)])?;
+                }
+                b'u' => {
+                    let items = pairs(self.pop_mark()?)?;
+                    self.set_items(items)?;
+                }
+
+                b'c' => {
+                    let module = self.line()?;
+                    let name = self.line()?;
+                    self.stack.push(Value::Global { module, name });
+                }
+                0x93 => {
+                    let name = self.pop()?;
+                    let module = self.pop()?;
+                    let (module, name) = match (self.resolved(module)?, self.resolved(name)?) {
+                        (Value::String(module), Value::String(name)) => (module, name),
+                        _ => return Err("Invalid STACK_GLOBAL arguments".to_string()),
+                    };
+                    self.stack.push(Value::Global { module, name });
+                }
+                b'R' | 0x81 => {
+                    let args = self.pop()?;
+                    let callable = self.pop()?;
+                    let value = self.call(callable, args)?;
+                    self.stack.push(value);
+                }
+                0x92 => {
+                    let _kwargs = self.pop()?;
+                    let args = self.pop()?;
+                    let callable = self.pop()?;
+                    let value = self.call(callable, args)?;
+                    self.stack.push(value);
+                }
+                b'i' => {
+                    let module = self.line()?;
+                    let name = self.line()?;
+                    let args = Value::Tuple(self.pop_mark()?);
+                    let value = self.call(Value::Global { module, name }, args)?;
+                    self.stack.push(value);
+                }
+                b'o' => {
+                    let mut items = self.pop_mark()?;
+                    if items.is_empty() {
+                        return Err("OBJ without class".to_string());
+                    }
+                    let callable = items.remove(0);
+                    let value = self.call(callable, Value::Tuple(items))?;
+                    self.stack.push(value);
+                }
+                b'b' => {
+                    let state = self.pop()?;
+                    let state = self.resolved(state)?;
+                    match self.target()? {
+                        Value::Object { state: s, .. } => **s = state,
+                        other => return Err(format!("BUILD on unsupported {}", other.type_name())),
+                    }
+                }
+
+                b'p' => {
+                    let id = parse_number(&self.line()?)? as u32;
+                    self.put(id)?;
+                }
+                b'q' => {
+                    let id = self.take(1)?[0] as u32;
+                    self.put(id)?;
+                }
+                b'r' => {
+                    let id = u32::from_le_bytes(self.array()?);
+                    self.put(id)?;
+                }
+                0x94 => {
+                    let id = self.memo.len() as u32;
+                    self.put(id)?;
+                }
+                b'g' => {
+                    let id = parse_number(&self.line()?)? as u32;
+                    self.get(id)?;
+                }
+                b'h' => {
+                    let id = self.take(1)?[0] as u32;
+                    self.get(id)?;
+                }
+                b'j' => {
+                    let id = u32::from_le_bytes(self.array()?);
+                    self.get(id)?;
+                }
+                _ => {
+                    return Err(format!(
+                        "Unsupported opcode {:#04x} at byte {}",
+                        opcode,
+                        self.pos - 1
+                    ))
+                }
+            }
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or("Unexpected end of pickle")?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn len4(&mut self) -> Result<usize, String> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+
+    fn len8(&mut self) -> Result<usize, String> {
+        Ok(u64::from_le_bytes(self.array()?) as usize)
+    }
+
+    fn line(&mut self) -> Result<String, String> {
+        let rest = &self.bytes[self.pos..];
+        let end = rest
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or("Unexpected end of pickle")?;
+        self.pos += end + 1;
+        let line = &rest[..end];
+        Ok(line.iter().map(|&b| b as char).collect())
+    }
+
+    fn pop(&mut self) -> Result<Value, String> {
+        self.stack
+            .pop()
+            .ok_or_else(|| "Stack underflow".to_string())
+    }
+
+    fn top(&mut self) -> Result<&mut Value, String> {
+        self.stack
+            .last_mut()
+            .ok_or_else(|| "Stack underflow".to_string())
+    }
+
+    fn pop_mark(&mut self) -> Result<Vec<Value>, String> {
+        let mark = self.marks.pop().ok_or("MARK missing")?;
+        if mark > self.stack.len() {
+            return Err("Stack underflow".to_string());
+        }
+        Ok(self.stack.split_off(mark))
+    }
+
+    /// The value on top of the stack, following a memo reference so modifications of a
+    /// memoized object are seen by every later reference to it.
+    fn target(&mut self) -> Result<&mut Value, String> {
+        match self.stack.last() {
+            Some(&Value::MemoRef(index)) => {
+                self.object(index)?;
+                Ok(&mut self.objects[index])
+            }
+            Some(_) => self.top(),
+            None => Err("Stack underflow".to_string()),
+        }
+    }
+
+    fn resolved(&self, value: Value) -> Result<Value, String> {
+        match value {
+            Value::MemoRef(index) => self.object(index).cloned(),
+            value => Ok(value),
+        }
+    }
+
+    /// The memoized object at `index`, which `put` never lets refer to another one.
+    fn object(&self, index: usize) -> Result<&Value, String> {
+        match self.objects.get(index) {
+            Some(Value::MemoRef(_)) => Err("Memo entry refers to another entry".to_string()),
+            Some(value) => Ok(value),
+            None => Err(format!("Memoized object {} missing", index)),
+        }
+    }
+
+    fn extend(&mut self, new_items: Vec<Value>) -> Result<(), String> {
+        match self.target()? {
+            Value::List(items) => items.extend(new_items),
+            Value::Object { items, .. } => {
+                items.extend(new_items.into_iter().map(|v| (Value::None, v)))
+            }
+            other => return Err(format!("APPEND to unsupported {}", other.type_name())),
+        }
+        Ok(())
+    }
+
+    fn set_items(&mut self, new_items: Vec<(Value, Value)>) -> Result<(), String> {
+        match self.target()? {
+            Value::Dict(items) | Value::Object { items, .. } => items.extend(new_items),
+            other => return Err(format!("SETITEM on unsupported {}", other.type_name())),
+        }
+        Ok(())
+    }
+
+    fn put(&mut self, id: u32) -> Result<(), String> {
+        let index = self.objects.len();
+        let top = self.top()?;
+        // Memoizing a reference again shares its object instead of chaining references.
+        let index = match *top {
+            Value::MemoRef(index) => index,
+            _ => {
+                let value = std::mem::replace(top, Value::MemoRef(index));
+                self.objects.push(value);
+                index
+            }
+        };
+        self.memo.insert(id, index);
+        Ok(())
+    }
+
+    fn get(&mut self, id: u32) -> Result<(), String> {
+        let index = *self
+            .memo
+            .get(&id)
+            .ok_or_else(|| format!("Memo entry {} missing", id))?;
+        self.stack.push(Value::MemoRef(index));
+        Ok(())
+    }
+
+    /// Apply `callable` to `args`. Apart from a few helpers used by `copyreg` and Python 3's
+    /// protocol 2 `bytes`, the call is only recorded.
+    fn call(&self, callable: Value, args: Value) -> Result<Value, String> {
+        let callable = self.resolved(callable)?;
+        let args = self.resolve(args, 0)?;
+        if let Value::Global { module, name } = &callable {
+            match (module.as_str(), name.as_str()) {
+                ("_codecs", "encode") => {
+                    if let Some([Value::String(s), ..]) = args.as_list() {
+                        return Ok(Value::Bytes(latin1(s)));
+                    }
+                }
+                ("copy_reg", "_reconstructor") | ("copyreg", "_reconstructor") => {
+                    if let Some([class, ..]) = args.as_list() {
+                        return Ok(object(class.clone(), Value::Tuple(Vec::new())));
+                    }
+                }
+                ("__builtin__", "bytes") | ("builtins", "bytes") => {
+                    if let Some([]) = args.as_list() {
+                        return Ok(Value::Bytes(Vec::new()));
+                    }
+                }
+                ("__builtin__", "set") | ("builtins", "set") => {
+                    if let Some([Value::List(items)]) = args.as_list() {
+                        return Ok(Value::List(items.clone()));
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(object(callable, args))
+    }
+
+    /// Replace all memo references in `value` by the memoized values.
+    fn resolve(&self, value: Value, depth: usize) -> Result<Value, String> {
+        if depth > 256 {
+            return Err("Pickle nested too deeply or recursive".to_string());
+        }
+        let resolve_all = |values: Vec<Value>| -> Result<Vec<Value>, String> {
+            values
+                .into_iter()
+                .map(|v| self.resolve(v, depth + 1))
+                .collect()
+        };
+        let resolve_pairs = |pairs: Vec<(Value, Value)>| -> Result<Vec<(Value, Value)>, String> {
+            pairs
+                .into_iter()
+                .map(|(k, v)| Ok((self.resolve(k, depth + 1)?, self.resolve(v, depth + 1)?)))
+                .collect()
+        };
+        Ok(match value {
+            Value::MemoRef(_) => self.resolve(self.resolved(value)?, depth + 1)?,
+            Value::List(items) => Value::List(resolve_all(items)?),
+            Value::Tuple(items) => Value::Tuple(resolve_all(items)?),
+            Value::Dict(items) => Value::Dict(resolve_pairs(items)?),
+            Value::Object {
+                callable,
+                args,
+                state,
+                items,
+            } => Value::Object {
+                callable: Box::new(self.resolve(*callable, depth + 1)?),
+                args: Box::new(self.resolve(*args, depth + 1)?),
+                state: Box::new(self.resolve(*state, depth + 1)?),
+                items: resolve_pairs(items)?,
+            },
+            value => value,
+        })
+    }
+}
+
+fn object(callable: Value, args: Value) -> Value {
+    Value::Object {
+        callable: Box::new(callable),
+        args: Box::new(args),
+        state: Box::new(Value::None),
+        items: Vec::new(),
+    }
+}
+
+fn pairs(items: Vec<Value>) -> Result<Vec<(Value, Value)>, String> {
+    let mut pairs = Vec::with_capacity(items.len() / 2);
+    let mut items = items.into_iter();
+    while let Some(key) = items.next() {
+        let value = items.next().ok_or("Odd number of dict items")?;
+        pairs.push((key, value));
+    }
+    Ok(pairs)
+}
+
+fn parse_number(text: &str) -> Result<i64, String> {
+    text.trim()
+        .parse::<i64>()
+        .map_err(|_| format!("Invalid integer {:?}", text))
+}
+
+/// Little endian two's complement integer of `LONG1`/`LONG4`.
+fn long(bytes: &[u8]) -> Result<i64, String> {
+    if bytes.len() > 8 {
+        return Err(format!("Integer of {} bytes too large", bytes.len()));
+    }
+    if bytes.is_empty() {
+        return Ok(0);
+    }
+    let mut value: u64 = 0;
+    for &byte in bytes.iter().rev() {
+        value = (value << 8) | byte as u64;
+    }
+    let shift = 64 - 8 * bytes.len() as u32;
+    Ok(if shift < 64 {
+        ((value << shift) as i64) >> shift
+    } else {
+        value as i64
+    })
+}
+
+/// Decode the quoted Python 2 `repr` of a `str` used by the `STRING` opcode.
+fn unquote(line: &str) -> Result<Vec<u8>, String> {
+    let quoted = line.trim_end();
+    let inner = if quoted.len() >= 2
+        && (quoted.starts_with('\'') && quoted.ends_with('\'')
+            || quoted.starts_with('"') && quoted.ends_with('"'))
+    {
+        &quoted[1..quoted.len() - 1]
+    } else {
+        return Err(format!("Invalid string {:?}", line));
+    };
+    let mut bytes = Vec::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            bytes.push(c as u32 as u8);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('t') => bytes.push(b'\t'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let byte = u8::from_str_radix(&hex, 16)
+                    .map_err(|_| format!("Invalid escape in {:?}", line))?;
+                bytes.push(byte);
+            }
+            Some(c) => bytes.push(c as u32 as u8),
+            None => return Err(format!("Invalid escape in {:?}", line)),
+        }
+    }
+    Ok(bytes)
+}
+
+/// Decode the `raw-unicode-escape` encoding of the `UNICODE` opcode.
+fn unescape_unicode(line: &str) -> Result<String, String> {
+    let mut text = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let len = match (c, chars.peek()) {
+            ('\\', Some('u')) => 4,
+            ('\\', Some('U')) => 8,
+            _ => {
+                text.push(c);
+                continue;
+            }
+        };
+        chars.next();
+        let hex: String = chars.by_ref().take(len).collect();
+        let c = u32::from_str_radix(&hex, 16)
+            .ok()
+            .and_then(std::char::from_u32)
+            .ok_or_else(|| format!("Invalid escape in {:?}", line))?;
+        text.push(c);
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // {'a': [1, 2, -3], 'b': 'text', 'c': (1.5, None, True)} as pickled by Python 3.
+    const DICT_V0: &[u8] =
+        b"(dp0\nVa\np1\n(lp2\nI1\naI2\naI-3\nasVb\np3\nVtext\np4\nsVc\np5\n(F1.5\nNI01\ntp6\ns.";
+    const DICT_V2: &[u8] = b"\x80\x02}q\x00(X\x01\x00\x00\x00aq\x01]q\x02(K\x01K\x02J\xfd\xff\xff\xffeX\x01\x00\x00\x00bq\x03X\x04\x00\x00\x00textq\x04X\x01\x00\x00\x00cq\x05G?\xf8\x00\x00\x00\x00\x00\x00N\x88\x87q\x06u.";
+    const DICT_V4: &[u8] = b"\x80\x04\x952\x00\x00\x00\x00\x00\x00\x00}\x94(\x8c\x01a\x94]\x94(K\x01K\x02J\xfd\xff\xff\xffe\x8c\x01b\x94\x8c\x04text\x94\x8c\x01c\x94G?\xf8\x00\x00\x00\x00\x00\x00N\x88\x87\x94u.";
+
+    // {'data': numpy.array([[1, -2], [3, 300]], dtype='<i2')}
+    const ARRAY_V2: &[u8] = b"\x80\x02}q\x00X\x04\x00\x00\x00dataq\x01cnumpy.core.multiarray\n_reconstruct\nq\x02cnumpy\nndarray\nq\x03K\x00\x85q\x04c_codecs\nencode\nq\x05X\x01\x00\x00\x00bq\x06X\x06\x00\x00\x00latin1q\x07\x86q\x08Rq\x09\x87q\x0aRq\x0b(K\x01K\x02K\x02\x86q\x0ccnumpy\ndtype\nq\x0dX\x02\x00\x00\x00i2q\x0e\x89\x88\x87q\x0fRq\x10(K\x03X\x01\x00\x00\x00<q\x11NNNJ\xff\xff\xff\xffJ\xff\xff\xff\xffK\x00tq\x12b\x89h\x05X\x0a\x00\x00\x00\x01\x00\xc3\xbe\xc3\xbf\x03\x00,\x01q\x13h\x07\x86q\x14Rq\x15tq\x16bs.";
+    const ARRAY_V4: &[u8] = b"\x80\x04\x95\x9c\x00\x00\x00\x00\x00\x00\x00}\x94\x8c\x04data\x94\x8c\x15numpy.core.multiarray\x94\x8c\x0c_reconstruct\x94\x93\x94\x8c\x05numpy\x94\x8c\x07ndarray\x94\x93\x94K\x00\x85\x94C\x01b\x94\x87\x94R\x94(K\x01K\x02K\x02\x86\x94h\x05\x8c\x05dtype\x94\x93\x94\x8c\x02i2\x94\x89\x88\x87\x94R\x94(K\x03\x8c\x01<\x94NNNJ\xff\xff\xff\xffJ\xff\xff\xff\xffK\x00t\x94b\x89C\x08\x01\x00\xfe\xff\x03\x00,\x01\x94t\x94bs.";
+
+    fn expected_dict() -> Value {
+        Value::Dict(vec![
+            (
+                Value::String("a".to_string()),
+                Value::List(vec![Value::Int(1), Value::Int(2), Value::Int(-3)]),
+            ),
+            (
+                Value::String("b".to_string()),
+                Value::String("text".to_string()),
+            ),
+            (
+                Value::String("c".to_string()),
+                Value::Tuple(vec![Value::Float(1.5), Value::None, Value::Bool(true)]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn dict_of_all_protocols() {
+        for bytes in &[DICT_V0, DICT_V2, DICT_V4] {
+            let value = from_slice(bytes).unwrap();
+            assert_eq!(value, expected_dict());
+            assert_eq!(
+                value.get("a").unwrap().to_int_vec().unwrap(),
+                vec![1, 2, -3]
+            );
+            assert_eq!(value.get("b").unwrap().as_str(), Some("text"));
+        }
+    }
+
+    #[test]
+    fn binbytes_and_memo() {
+        // {'data': b'\x00\x01\xff', 'labels': [<memo of data>, <memo of data>]}, protocol 3
+        let bytes = b"\x80\x03}q\x00(X\x04\x00\x00\x00dataq\x01C\x03\x00\x01\xffq\x02X\x06\x00\x00\x00labelsq\x03]q\x04(h\x02h\x02eu.";
+        let value = from_slice(bytes).unwrap();
+        let data = Value::Bytes(vec![0, 1, 255]);
+        assert_eq!(value.get("data"), Some(&data));
+        assert_eq!(
+            value.get("labels"),
+            Some(&Value::List(vec![data.clone(), data]))
+        );
+
+        // [x, x] with x = [1, 2] shared through the memo, protocol 2
+        let value = from_slice(b"\x80\x02]q\x00(]q\x01(K\x01K\x02eh\x01e.").unwrap();
+        let inner = Value::List(vec![Value::Int(1), Value::Int(2)]);
+        assert_eq!(value, Value::List(vec![inner.clone(), inner]));
+    }
+
+    #[test]
+    fn numpy_array() {
+        for bytes in &[ARRAY_V2, ARRAY_V4] {
+            let array = from_slice(bytes)
+                .unwrap()
+                .get("data")
+                .unwrap()
+                .to_ndarray()
+                .unwrap();
+            assert_eq!(array.shape, vec![2, 2]);
+            assert_eq!(array.dtype, DType::parse("<i2").unwrap());
+            assert!(!array.fortran_order);
+            let values = array.to_array::<i16>().unwrap();
+            assert_eq!(values.into_raw_vec(), vec![1, -2, 3, 300]);
+        }
+    }
+
+    #[test]
+    fn truncated_input() {
+        for bytes in &[DICT_V0, DICT_V2, DICT_V4, ARRAY_V2, ARRAY_V4] {
+            for len in 0..bytes.len() {
+                assert!(
+                    from_slice(&bytes[..len]).is_err(),
+                    "prefix of {} bytes",
+                    len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn overlong_lengths() {
+        // BINUNICODE8, BINBYTES8 and BINUNICODE with lengths beyond the end of the input
+        let mut unicode8 = b"\x80\x04\x8d".to_vec();
+        unicode8.extend_from_slice(&u64::MAX.to_le_bytes());
+        let mut bytes8 = b"\x80\x04\x8e".to_vec();
+        bytes8.extend_from_slice(&(u64::MAX - 2).to_le_bytes());
+        let unicode = b"\x80\x02X\xff\xff\xff\xffabc.".to_vec();
+        for bytes in &[unicode8, bytes8, unicode] {
+            assert_eq!(
+                from_slice(bytes),
+                Err("Unexpected end of pickle".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn memo_references_to_memo_references() {
+        // An empty list memoized as 0, then 1 and again 0 while a reference is on the stack.
+        let value = from_slice(b"\x80\x02]q\x00q\x01q\x00.");
+        assert_eq!(value, Ok(Value::List(Vec::new())));
+        let value = from_slice(b"\x80\x02]q\x00q\x01q\x00K\x01a.");
+        assert_eq!(value, Ok(Value::List(vec![Value::Int(1)])));
+        // A list containing itself
+        assert!(from_slice(b"\x80\x02]q\x00h\x00a.").is_err());
+    }
+
+    #[test]
+    fn invalid_numpy_shapes() {
+        let array = from_slice(ARRAY_V4).unwrap().get("data").unwrap().clone();
+        let with_shape = |shape: Vec<i64>| match array.clone() {
+            Value::Object {
+                callable,
+                args,
+                state,
+                items,
+            } => {
+                let mut state = state.as_list().unwrap().to_vec();
+                state[1] = Value::Tuple(shape.into_iter().map(Value::Int).collect());
+                Value::Object {
+                    callable,
+                    args,
+                    state: Box::new(Value::Tuple(state)),
+                    items,
+                }
+            }
+            _ => unreachable!(),
+        };
+        assert!(with_shape(vec![4]).to_ndarray().is_ok());
+        for shape in &[
+            vec![-2, -1],
+            vec![2, 3],
+            vec![i64::MAX, i64::MAX],
+            vec![1 << 62, 4],
+        ] {
+            assert!(
+                with_shape(shape.clone()).to_ndarray().is_err(),
+                "shape {:?}",
+                shape
+            );
+        }
+    }
+
+    #[test]
+    fn long_integers() {
+        assert_eq!(long(&[]), Ok(0));
+        assert_eq!(long(&[0xff]), Ok(-1));
+        assert_eq!(long(&[0x00, 0x80]), Ok(-32768));
+        assert_eq!(long(&[0xff, 0x00]), Ok(255));
+        assert!(long(&[0; 9]).is_err());
+    }
+}
@@ -0,0 +1,329 @@
+use ndarray::prelude::*;
+use rayon::prelude::*;
+
+/// Rows of the data matrix that are converted to `f64` at once by `covariance`.
+static CHUNK_ROWS: usize = 2048;
+/// Columns of the covariance matrix that are computed per task.
+static BLOCK_COLS: usize = 128;
+/// Average number of QL iterations per eigenvalue before `tql2` gives up, 30 in EISPACK.
+static MAX_SWEEPS: usize = 30;
+
+/// Covariance `x^T x / n` of the already centered rows of `x`, accumulated in `f64`.
+pub fn covariance(x: &ArrayView2<f32>) -> Array2<f64> {
+    let (n, d) = x.dim();
+    let mut cov: Array2<f64> = Array2::zeros((d, d));
+    for chunk in x.axis_chunks_iter(Axis(0), CHUNK_ROWS) {
+        let chunk = chunk.mapv(f64::from);
+        let blocks: Vec<(usize, ArrayViewMut2<f64>)> = cov
+            .axis_chunks_iter_mut(Axis(1), BLOCK_COLS)
+            .enumerate()
+            .collect();
+        blocks.into_par_iter().for_each(|(b, mut block)| {
+            let start = b * BLOCK_COLS;
+            let columns = chunk.slice(s![.., start..start + block.ncols()]);
+            block += &chunk.t().dot(&columns);
+        });
+    }
+    cov / n.max(1) as f64
+}
+
+/// Eigendecomposition of the symmetric matrix `a` by Householder tridiagonalization and the
+/// implicit QL algorithm (`tred2` and `tql2` of EISPACK, following the JAMA port).
+///
+/// Returns the eigenvalues in descending order and the matching unit eigenvectors as rows.
+/// Panics if `a` has NaN or infinite entries, e.g. from such pixels in the fitted images.
+pub fn symmetric_eigen(a: &Array2<f64>) -> (Array1<f64>, Array2<f64>) {
+    let n = a.nrows();
+    assert!(
+        a.ncols() == n,
+        "Expected a square matrix, got {:?}.",
+        a.dim()
+    );
+    assert!(
+        a.iter().all(|x| x.is_finite()),
+        "Cannot diagonalize a matrix with NaN or infinite entries."
+    );
+    if n == 0 {
+        return (Array1::zeros(0), Array2::zeros((0, 0)));
+    }
+    // Both steps work on the transposed eigenvector matrix, which starts out as `a` itself.
+    let mut vt = a.as_standard_layout().into_owned();
+    let mut d = vec![0.; n];
+    let mut e = vec![0.; n];
+    tred2(&mut vt, &mut d, &mut e);
+    tql2(&mut vt, &mut d, &mut e);
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| d[j].total_cmp(&d[i]));
+    let values = order.iter().map(|&i| d[i]).collect();
+    (values, vt.select(Axis(0), &order))
+}
+
+/// Reduce the symmetric matrix `vt` to tridiagonal form with diagonal `d` and subdiagonal
+/// `e`. The accumulated transformations are left transposed in `vt`, so that the inner loops
+/// run along rows.
+#[allow(clippy::needless_range_loop)]
+fn tred2(vt: &mut Array2<f64>, d: &mut [f64], e: &mut [f64]) {
+    let n = d.len();
+    for j in 0..n {
+        d[j] = vt[[j, n - 1]];
+    }
+    for i in (1..n).rev() {
+        let mut scale = 0.;
+        let mut h = 0.;
+        for k in 0..i {
+            scale += d[k].abs();
+        }
+        if scale == 0. {
+            e[i] = d[i - 1];
+            for j in 0..i {
+                d[j] = vt[[j, i - 1]];
+                vt[[j, i]] = 0.;
+                vt[[i, j]] = 0.;
+            }
+        } else {
+            for k in 0..i {
+                d[k] /= scale;
+                h += d[k] * d[k];
+            }
+            let mut f = d[i - 1];
+            let mut g = h.sqrt();
+            if f > 0. {
+                g = -g;
+            }
+            e[i] = scale * g;
+            h -= f * g;
+            d[i - 1] = f - g;
+            for x in e.iter_mut().take(i) {
+                *x = 0.;
+            }
+            for j in 0..i {
+                f = d[j];
+                vt[[i, j]] = f;
+                g = e[j] + vt[[j, j]] * f;
+                for k in j + 1..i {
+                    g += vt[[j, k]] * d[k];
+                    e[k] += vt[[j, k]] * f;
+                }
+                e[j] = g;
+            }
+            f = 0.;
+            for j in 0..i {
+                e[j] /= h;
+                f += e[j] * d[j];
+            }
+            let hh = f / (h + h);
+            for j in 0..i {
+                e[j] -= hh * d[j];
+            }
+            for j in 0..i {
+                f = d[j];
+                g = e[j];
+                for k in j..i {
+                    vt[[j, k]] -= f * e[k] + g * d[k];
+                }
+                d[j] = vt[[j, i - 1]];
+                vt[[j, i]] = 0.;
+            }
+        }
+        d[i] = h;
+    }
+    for i in 0..n - 1 {
+        vt[[i, n - 1]] = vt[[i, i]];
+        vt[[i, i]] = 1.;
+        let h = d[i + 1];
+        if h != 0. {
+            for k in 0..=i {
+                d[k] = vt[[i + 1, k]] / h;
+            }
+            for j in 0..=i {
+                let mut g = 0.;
+                for k in 0..=i {
+                    g += vt[[i + 1, k]] * vt[[j, k]];
+                }
+                for k in 0..=i {
+                    vt[[j, k]] -= g * d[k];
+                }
+            }
+        }
+        for k in 0..=i {
+            vt[[i + 1, k]] = 0.;
+        }
+    }
+    for j in 0..n {
+        d[j] = vt[[j, n - 1]];
+        vt[[j, n - 1]] = 0.;
+    }
+    vt[[n - 1, n - 1]] = 1.;
+    e[0] = 0.;
+}
+
+/// Diagonalize the tridiagonal matrix of `tred2`, `vt` holds the transposed transformations.
+/// Panics if the iteration does not converge within `MAX_SWEEPS * n` steps.
+#[allow(clippy::needless_range_loop)]
+fn tql2(vt: &mut Array2<f64>, d: &mut [f64], e: &mut [f64]) {
+    let n = d.len();
+    let mut iterations = 0;
+    for i in 1..n {
+        e[i - 1] = e[i];
+    }
+    e[n - 1] = 0.;
+    let mut f = 0.;
+    let mut tst1: f64 = 0.;
+    let eps = f64::EPSILON;
+    for l in 0..n {
+        tst1 = tst1.max(d[l].abs() + e[l].abs());
+        let mut m = l;
+        while m < n - 1 && e[m].abs() > eps * tst1 {
+            m += 1;
+        }
+        if m > l {
+            loop {
+                iterations += 1;
+                assert!(
+                    iterations <= MAX_SWEEPS * n,
+                    "Eigendecomposition did not converge after {} iterations.",
+                    MAX_SWEEPS * n
+                );
+                let mut g = d[l];
+                let mut p = (d[l + 1] - g) / (2. * e[l]);
+                let mut r = p.hypot(1.);
+                if p < 0. {
+                    r = -r;
+                }
+                d[l] = e[l] / (p + r);
+                d[l + 1] = e[l] * (p + r);
+                let dl1 = d[l + 1];
+                let mut h = g - d[l];
+                for x in d.iter_mut().skip(l + 2) {
+                    *x -= h;
+                }
+                f += h;
+
+                p = d[m];
+                let mut c = 1.;
+                let mut c2 = c;
+                let mut c3 = c;
+                let el1 = e[l + 1];
+                let mut s = 0.;
+                let mut s2 = 0.;
+                for i in (l..m).rev() {
+                    c3 = c2;
+                    c2 = c;
+                    s2 = s;
+                    g = c * e[i];
+                    h = c * p;
+                    r = p.hypot(e[i]);
+                    e[i + 1] = s * r;
+                    s = e[i] / r;
+                    c = p / r;
+                    p = c * d[i] - s * g;
+                    d[i + 1] = h + s * (c * g + s * d[i]);
+                    let (mut upper, mut lower) = vt.multi_slice_mut((s![i, ..], s![i + 1, ..]));
+                    for (a, b) in upper.iter_mut().zip(lower.iter_mut()) {
+                        let h = *b;
+                        *b = s * *a + c * h;
+                        *a = c * *a - s * h;
+                    }
+                }
+                p = -s * s2 * c3 * el1 * e[l] / dl1;
+                e[l] = s * p;
+                d[l] = c * p;
+                if e[l].abs() <= eps * tst1 {
+                    break;
+                }
+            }
+        }
+        d[l] += f;
+        e[l] = 0.;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Checks that the rows of `vectors` are orthonormal eigenvectors of `a` to the descending
+    /// `values`.
+    fn check_decomposition(a: &Array2<f64>, values: &Array1<f64>, vectors: &Array2<f64>) {
+        let n = a.nrows();
+        assert_eq!(values.len(), n);
+        assert_eq!(vectors.dim(), (n, n));
+        assert!(values.windows(2).into_iter().all(|w| w[0] >= w[1]));
+        let tolerance = 1e-10 * (1. + a.iter().fold(0f64, |m, x| m.max(x.abs())));
+        let gram = vectors.dot(&vectors.t());
+        for ((i, j), &x) in gram.indexed_iter() {
+            let expected = if i == j { 1. } else { 0. };
+            assert!((x - expected).abs() < 1e-10, "v{} . v{} = {}", i, j, x);
+        }
+        for (v, &lambda) in vectors.outer_iter().zip(values) {
+            let residual = a.dot(&v) - &v * lambda;
+            assert!(
+                residual.iter().all(|r| r.abs() < tolerance),
+                "{:?}",
+                residual
+            );
+        }
+    }
+
+    #[test]
+    fn rank_two_matrix() {
+        let a = arr2(&[[2., 1., 0.], [1., 2., 0.], [0., 0., 0.]]);
+        let (values, vectors) = symmetric_eigen(&a);
+        check_decomposition(&a, &values, &vectors);
+        for (value, expected) in values.iter().zip(&[3., 1., 0.]) {
+            assert!((value - expected).abs() < 1e-12);
+        }
+        // Eigenvectors are unique up to their sign.
+        let h = 0.5f64.sqrt();
+        let expected = arr2(&[[h, h, 0.], [h, -h, 0.], [0., 0., 1.]]);
+        for (v, e) in vectors.outer_iter().zip(expected.outer_iter()) {
+            assert!((v.dot(&e).abs() - 1.).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn random_and_degenerate_matrices() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let b = Array2::from_shape_simple_fn((24, 24), || rng.gen_range(-1.0..1.0));
+        let random = &b + &b.t();
+        let diagonal = Array2::from_diag(&arr1(&[0.5, -2., 3., 0.5, 0.]));
+        let identity = Array2::eye(6);
+        for a in &[
+            random,
+            diagonal,
+            identity,
+            Array2::zeros((4, 4)),
+            arr2(&[[7.]]),
+        ] {
+            let (values, vectors) = symmetric_eigen(a);
+            check_decomposition(a, &values, &vectors);
+        }
+        let (values, _) = symmetric_eigen(&Array2::from_diag(&arr1(&[0.5, -2., 3., 0.5, 0.])));
+        assert_eq!(values, arr1(&[3., 0.5, 0.5, 0., -2.]));
+        let (values, vectors) = symmetric_eigen(&Array2::zeros((0, 0)));
+        assert_eq!((values.len(), vectors.dim()), (0, (0, 0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "NaN or infinite")]
+    fn rejects_nan() {
+        symmetric_eigen(&arr2(&[[1., f64::NAN], [f64::NAN, 1.]]));
+    }
+
+    #[test]
+    fn covariance_of_centered_rows() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let x = Array2::from_shape_simple_fn((CHUNK_ROWS + 37, BLOCK_COLS + 5), || {
+            rng.gen_range(-1.0f32..1.0)
+        });
+        let cov = covariance(&x.view());
+        let x = x.mapv(f64::from);
+        let expected = x.t().dot(&x) / x.nrows() as f64;
+        assert!(cov
+            .iter()
+            .zip(&expected)
+            .all(|(a, b)| (a - b).abs() < 1e-10));
+    }
+}
@@ -0,0 +1,496 @@
+//! Preprocessing fitted on the training images and applied to any split.
+//!
+//! Every preprocessor flattens the images to `(n, features)`, so it works on the NHW images of
+//! `mnist_builder::Data`, the NCHW images of `cifar_builder::Data` or plain feature matrices.
+//! Fitted parameters can be stored with `save_npz` and restored with `load_npz` to apply the
+//! same preprocessing at inference time.
+//!
+//! ```no_run
+//! use datasets::cifar10;
+//! use datasets::preprocess::{Gcn, Zca};
+//!
+//! let data = cifar10::new();
+//! // The pylearn2 CIFAR-10 pipeline of the maxout paper.
+//! let gcn = Gcn::new(55.).min_divisor(1e-8);
+//! let trn_img = gcn.transform(&data.trn_img);
+//! let tst_img = gcn.transform(&data.tst_img);
+//! let zca = Zca::fit(&trn_img, 1e-2);
+//! let trn_img = zca.transform(&trn_img);
+//! let tst_img = zca.transform(&tst_img);
+//! zca.save_npz("zca.npz").unwrap();
+//! ```
+
+mod linalg;
+
+use ndarray::prelude::*;
+use ndarray::{Data, Ix1, Ix2};
+use std::path::Path;
+
+use crate::npy::{NpzReader, NpzWriter};
+
+/// Number of samples and features of `images`, whose first axis indexes samples.
+fn flat_dim<S: Data<Elem = f32>, D: Dimension>(images: &ArrayBase<S, D>) -> (usize, usize) {
+    let shape = images.shape();
+    assert!(!shape.is_empty(), "Images need a sample axis.");
+    (shape[0], shape[1..].iter().product())
+}
+
+/// Copy `images` into a `(n, features)` matrix.
+fn flatten<S: Data<Elem = f32>, D: Dimension>(images: &ArrayBase<S, D>) -> Array2<f32> {
+    let dim = flat_dim(images);
+    images
+        .as_standard_layout()
+        .into_owned()
+        .into_shape(dim)
+        .unwrap()
+}
+
+fn check_features(expected: usize, got: usize) {
+    assert!(
+        expected == got,
+        "Fitted on {} features per sample, got {}.",
+        expected,
+        got
+    );
+}
+
+fn read_array<D: Dimension>(
+    npz: &mut NpzReader<impl std::io::Read + std::io::Seek>,
+    name: &str,
+) -> Result<Array<f32, D>, String> {
+    npz.by_name::<f32>(name)?
+        .into_dimensionality::<D>()
+        .map_err(|e| format!("Unexpected shape of {}: {:?}", name, e))
+}
+
+fn read_scalar(
+    npz: &mut NpzReader<impl std::io::Read + std::io::Seek>,
+    name: &str,
+) -> Result<f32, String> {
+    let array = read_array::<Ix1>(npz, name)?;
+    array
+        .first()
+        .cloned()
+        .ok_or_else(|| format!("{} is empty", name))
+}
+
+/// Global contrast normalization: every sample is centered on its own mean and scaled to
+/// the norm `scale`, as in pylearn2's `global_contrast_normalize`.
+///
+/// The divisor is `sqrt(sqrt_bias + sum(x^2)) / scale` (or `sqrt(sqrt_bias + var(x)) / scale`
+/// with `use_std(true)`), divisors below `min_divisor` are replaced by 1. Nothing is fitted,
+/// the same object can be applied to every split.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gcn {
+    scale: f32,
+    sqrt_bias: f32,
+    min_divisor: f32,
+    use_std: bool,
+}
+
+impl Gcn {
+    pub fn new(scale: f32) -> Gcn {
+        Gcn {
+            scale,
+            sqrt_bias: 0.,
+            min_divisor: 1e-8,
+            use_std: false,
+        }
+    }
+
+    /// Added to the squared norm before taking the root, 0 by default.
+    pub fn sqrt_bias(mut self, sqrt_bias: f32) -> Gcn {
+        self.sqrt_bias = sqrt_bias;
+        self
+    }
+
+    /// Divisors below this value are replaced by 1, 1e-8 by default.
+    pub fn min_divisor(mut self, min_divisor: f32) -> Gcn {
+        self.min_divisor = min_divisor;
+        self
+    }
+
+    /// Divide by the standard deviation instead of the norm of every sample.
+    pub fn use_std(mut self, use_std: bool) -> Gcn {
+        self.use_std = use_std;
+        self
+    }
+
+    pub fn transform<S: Data<Elem = f32>, D: Dimension>(
+        &self,
+        images: &ArrayBase<S, D>,
+    ) -> Array<f32, D> {
+        let mut x = flatten(images);
+        let features = x.ncols().max(1) as f32;
+        for mut row in x.outer_iter_mut() {
+            let mean = row.sum() / features;
+            row.mapv_inplace(|v| v - mean);
+            let squares = row.iter().map(|v| v * v).sum::<f32>();
+            let squares = if self.use_std {
+                squares / features
+            } else {
+                squares
+            };
+            let mut divisor = (self.sqrt_bias + squares).sqrt() / self.scale;
+            if divisor < self.min_divisor {
+                divisor = 1.;
+            }
+            row.mapv_inplace(|v| v / divisor);
+        }
+        x.into_shape(images.raw_dim()).unwrap()
+    }
+
+    pub fn save_npz<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let mut npz = NpzWriter::create(path, false)?;
+        npz.add_array("scale", &arr1(&[self.scale]))?;
+        npz.add_array("sqrt_bias", &arr1(&[self.sqrt_bias]))?;
+        npz.add_array("min_divisor", &arr1(&[self.min_divisor]))?;
+        npz.add_array("use_std", &arr1(&[self.use_std as u8 as f32]))?;
+        npz.finish()?;
+        Ok(())
+    }
+
+    pub fn load_npz<P: AsRef<Path>>(path: P) -> Result<Gcn, String> {
+        let mut npz = NpzReader::open(path)?;
+        Ok(Gcn {
+            scale: read_scalar(&mut npz, "scale")?,
+            sqrt_bias: read_scalar(&mut npz, "sqrt_bias")?,
+            min_divisor: read_scalar(&mut npz, "min_divisor")?,
+            use_std: read_scalar(&mut npz, "use_std")? != 0.,
+        })
+    }
+}
+
+/// Per-feature standardization: every pixel is shifted and scaled to zero mean and unit
+/// variance over the training set. Constant pixels are only centered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standardize {
+    pub mean: Array1<f32>,
+    pub std: Array1<f32>,
+}
+
+impl Standardize {
+    pub fn fit<S: Data<Elem = f32>, D: Dimension>(images: &ArrayBase<S, D>) -> Standardize {
+        let x = flatten(images);
+        let n = x.nrows().max(1) as f64;
+        let mut sum: Array1<f64> = Array1::zeros(x.ncols());
+        let mut squares: Array1<f64> = Array1::zeros(x.ncols());
+        for row in x.outer_iter() {
+            sum.zip_mut_with(&row, |s, &v| *s += v as f64);
+            squares.zip_mut_with(&row, |s, &v| *s += v as f64 * v as f64);
+        }
+        let mean = &sum / n;
+        let var = (squares / n - &mean * &mean).mapv(|v| v.max(0.));
+        Standardize {
+            mean: mean.mapv(|v| v as f32),
+            std: var.mapv(|v| if v > 0. { v.sqrt() as f32 } else { 1. }),
+        }
+    }
+
+    pub fn transform<S: Data<Elem = f32>, D: Dimension>(
+        &self,
+        images: &ArrayBase<S, D>,
+    ) -> Array<f32, D> {
+        let mut x = flatten(images);
+        check_features(self.mean.len(), x.ncols());
+        x -= &self.mean;
+        x /= &self.std;
+        x.into_shape(images.raw_dim()).unwrap()
+    }
+
+    /// Undo `transform`.
+    pub fn inverse_transform<S: Data<Elem = f32>, D: Dimension>(
+        &self,
+        images: &ArrayBase<S, D>,
+    ) -> Array<f32, D> {
+        let mut x = flatten(images);
+        check_features(self.mean.len(), x.ncols());
+        x *= &self.std;
+        x += &self.mean;
+        x.into_shape(images.raw_dim()).unwrap()
+    }
+
+    pub fn save_npz<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let mut npz = NpzWriter::create(path, false)?;
+        npz.add_array("mean", &self.mean)?;
+        npz.add_array("std", &self.std)?;
+        npz.finish()?;
+        Ok(())
+    }
+
+    pub fn load_npz<P: AsRef<Path>>(path: P) -> Result<Standardize, String> {
+        let mut npz = NpzReader::open(path)?;
+        Ok(Standardize {
+            mean: read_array(&mut npz, "mean")?,
+            std: read_array(&mut npz, "std")?,
+        })
+    }
+}
+
+/// Mean of every feature and the centered samples.
+fn center(mut images: Array2<f32>) -> (Array1<f32>, Array2<f32>) {
+    let mut sum: Array1<f64> = Array1::zeros(images.ncols());
+    for row in images.outer_iter() {
+        sum.zip_mut_with(&row, |s, &v| *s += v as f64);
+    }
+    let mean = (sum / images.nrows().max(1) as f64).mapv(|v| v as f32);
+    images -= &mean;
+    (mean, images)
+}
+
+/// Principal component analysis: projects the centered samples onto the `n_components`
+/// directions of largest variance of the training set.
+///
+/// Fitting diagonalizes the `features x features` covariance matrix, which takes a while for
+/// the 3072 features of CIFAR.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pca {
+    pub mean: Array1<f32>,
+    /// Principal axes as rows, ordered by decreasing variance.
+    pub components: Array2<f32>,
+    /// Variance of the training set along every component.
+    pub explained_variance: Array1<f32>,
+    /// Fraction of the total variance of the training set along every component.
+    pub explained_variance_ratio: Array1<f32>,
+}
+
+impl Pca {
+    pub fn fit<S: Data<Elem = f32>, D: Dimension>(
+        images: &ArrayBase<S, D>,
+        n_components: usize,
+    ) -> Pca {
+        let (mean, x) = center(flatten(images));
+        assert!(
+            n_components <= x.ncols(),
+            "Can't extract {} components from {} features.",
+            n_components,
+            x.ncols()
+        );
+        let cov = linalg::covariance(&x.view());
+        let total_variance = cov.diag().sum();
+        let (values, vectors) = linalg::symmetric_eigen(&cov);
+        let values = values.slice(s![..n_components]).mapv(|v| v.max(0.));
+        Pca {
+            mean,
+            components: vectors.slice(s![..n_components, ..]).mapv(|v| v as f32),
+            explained_variance: values.mapv(|v| v as f32),
+            explained_variance_ratio: values
+                .mapv(|v| (v / total_variance.max(f64::MIN_POSITIVE)) as f32),
+        }
+    }
+
+    /// Project the samples to `(n, n_components)`.
+    pub fn transform<S: Data<Elem = f32>, D: Dimension>(
+        &self,
+        images: &ArrayBase<S, D>,
+    ) -> Array2<f32> {
+        let x = flatten(images);
+        check_features(self.mean.len(), x.ncols());
+        (x - &self.mean).dot(&self.components.t())
+    }
+
+    /// Map projected samples back to `(n, features)`.
+    pub fn inverse_transform(&self, projected: &Array2<f32>) -> Array2<f32> {
+        projected.dot(&self.components) + &self.mean
+    }
+
+    pub fn save_npz<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let mut npz = NpzWriter::create(path, false)?;
+        npz.add_array("mean", &self.mean)?;
+        npz.add_array("components", &self.components)?;
+        npz.add_array("explained_variance", &self.explained_variance)?;
+        npz.add_array("explained_variance_ratio", &self.explained_variance_ratio)?;
+        npz.finish()?;
+        Ok(())
+    }
+
+    pub fn load_npz<P: AsRef<Path>>(path: P) -> Result<Pca, String> {
+        let mut npz = NpzReader::open(path)?;
+        Ok(Pca {
+            mean: read_array(&mut npz, "mean")?,
+            components: read_array::<Ix2>(&mut npz, "components")?,
+            explained_variance: read_array(&mut npz, "explained_variance")?,
+            explained_variance_ratio: read_array(&mut npz, "explained_variance_ratio")?,
+        })
+    }
+}
+
+/// ZCA whitening: decorrelates the features of the training set while staying as close as
+/// possible to the original images. `epsilon` is added to the eigenvalues of the covariance
+/// matrix to avoid amplifying noise, common values are between 1e-5 and 1e-1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Zca {
+    pub mean: Array1<f32>,
+    /// Symmetric `features x features` whitening matrix.
+    pub whitening: Array2<f32>,
+}
+
+impl Zca {
+    pub fn fit<S: Data<Elem = f32>, D: Dimension>(images: &ArrayBase<S, D>, epsilon: f32) -> Zca {
+        let (mean, x) = center(flatten(images));
+        let (values, vectors) = linalg::symmetric_eigen(&linalg::covariance(&x.view()));
+        let scale = values.mapv(|v| 1. / (v.max(0.) + epsilon as f64).sqrt());
+        let whitening = vectors.t().dot(&(&vectors * &scale.insert_axis(Axis(1))));
+        Zca {
+            mean,
+            whitening: whitening.mapv(|v| v as f32),
+        }
+    }
+
+    pub fn transform<S: Data<Elem = f32>, D: Dimension>(
+        &self,
+        images: &ArrayBase<S, D>,
+    ) -> Array<f32, D> {
+        let x = flatten(images);
+        check_features(self.mean.len(), x.ncols());
+        (x - &self.mean)
+            .dot(&self.whitening)
+            .into_shape(images.raw_dim())
+            .unwrap()
+    }
+
+    pub fn save_npz<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let mut npz = NpzWriter::create(path, false)?;
+        npz.add_array("mean", &self.mean)?;
+        npz.add_array("whitening", &self.whitening)?;
+        npz.finish()?;
+        Ok(())
+    }
+
+    pub fn load_npz<P: AsRef<Path>>(path: P) -> Result<Zca, String> {
+        let mut npz = NpzReader::open(path)?;
+        Ok(Zca {
+            mean: read_array(&mut npz, "mean")?,
+            whitening: read_array(&mut npz, "whitening")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// Full-rank images of 2 x 2 x 2 pixels with strongly correlated pixels.
+    fn correlated_images() -> Array4<f32> {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sources = Array2::from_shape_simple_fn((4000, 8), || rng.gen_range(-1.0f32..1.0));
+        let mixing = Array2::from_shape_simple_fn((8, 8), || rng.gen_range(0.0f32..1.0));
+        (sources.dot(&mixing) + 3.)
+            .into_shape((4000, 2, 2, 2))
+            .unwrap()
+    }
+
+    #[test]
+    fn zca_gives_identity_covariance() {
+        let images = correlated_images();
+        let whitened = Zca::fit(&images, 1e-9).transform(&images);
+        assert_eq!(whitened.dim(), images.dim());
+        let x = flatten(&whitened);
+        let mean = x.mean_axis(Axis(0)).unwrap();
+        assert!(mean.iter().all(|m| m.abs() < 1e-4));
+        let cov = linalg::covariance(&x.view());
+        for ((i, j), &c) in cov.indexed_iter() {
+            let expected = if i == j { 1. } else { 0. };
+            assert!((c - expected).abs() < 1e-3, "cov[{}, {}] = {}", i, j, c);
+        }
+    }
+
+    #[test]
+    fn pca_with_all_components_reconstructs() {
+        let images = correlated_images();
+        let pca = Pca::fit(&images, 8);
+        let total: f32 = pca.explained_variance_ratio.sum();
+        assert!((total - 1.).abs() < 1e-4);
+        let projected = pca.transform(&images);
+        let restored = pca.inverse_transform(&projected);
+        let original = flatten(&images);
+        assert!(restored
+            .iter()
+            .zip(&original)
+            .all(|(a, b)| (a - b).abs() < 1e-3));
+    }
+
+    #[test]
+    fn standardize_gives_zero_mean_and_unit_variance() {
+        let mut images = correlated_images();
+        images.slice_mut(s![.., 0, 0, 0]).fill(2.);
+        let standardize = Standardize::fit(&images);
+        let x = flatten(&standardize.transform(&images));
+        let mean = x.mean_axis(Axis(0)).unwrap();
+        let var = x.mapv(|v| v * v).mean_axis(Axis(0)).unwrap();
+        assert!(mean.iter().all(|m| m.abs() < 1e-4));
+        // The constant pixel is only centered.
+        assert_eq!(var[0], 0.);
+        assert!(var.iter().skip(1).all(|v| (v - 1.).abs() < 1e-3));
+        let restored = standardize.inverse_transform(&standardize.transform(&images));
+        assert!(restored
+            .iter()
+            .zip(&images)
+            .all(|(a, b)| (a - b).abs() < 1e-4));
+    }
+
+    #[test]
+    fn gcn_centers_and_scales_every_sample() {
+        let mut images = correlated_images().slice(s![..50, .., .., ..]).to_owned();
+        images.index_axis_mut(Axis(0), 0).fill(5.);
+        for &(scale, use_std) in &[(1., false), (55., false), (1., true)] {
+            let x = flatten(&Gcn::new(scale).use_std(use_std).transform(&images));
+            for (i, row) in x.outer_iter().enumerate() {
+                assert!(row.sum().abs() < 1e-3);
+                let norm = row.dot(&row).sqrt();
+                let expected = match i {
+                    // A constant sample has a divisor of 0, which is replaced by 1.
+                    0 => 0.,
+                    _ if use_std => scale * (row.len() as f32).sqrt(),
+                    _ => scale,
+                };
+                assert!(
+                    (norm - expected).abs() < 1e-3 * scale,
+                    "{} != {}",
+                    norm,
+                    expected
+                );
+            }
+        }
+        // sqrt_bias shrinks the norm of low contrast samples.
+        let x = flatten(&Gcn::new(1.).sqrt_bias(10.).transform(&images));
+        assert!(x.outer_iter().skip(1).all(|row| row.dot(&row) < 1.));
+    }
+
+    #[test]
+    fn fitted_preprocessors_round_trip_through_npz() {
+        let images = correlated_images();
+        let dir = std::env::temp_dir();
+        let path = |name: &str| dir.join(format!("{}-{}.npz", name, std::process::id()));
+
+        let gcn = Gcn::new(55.).sqrt_bias(10.).min_divisor(1e-4).use_std(true);
+        gcn.save_npz(path("gcn")).unwrap();
+        let loaded = Gcn::load_npz(path("gcn")).unwrap();
+        assert_eq!(loaded, gcn);
+        assert_eq!(loaded.transform(&images), gcn.transform(&images));
+
+        let standardize = Standardize::fit(&images);
+        standardize.save_npz(path("standardize")).unwrap();
+        let loaded = Standardize::load_npz(path("standardize")).unwrap();
+        assert_eq!(loaded, standardize);
+        assert_eq!(loaded.transform(&images), standardize.transform(&images));
+
+        let pca = Pca::fit(&images, 3);
+        pca.save_npz(path("pca")).unwrap();
+        let loaded = Pca::load_npz(path("pca")).unwrap();
+        assert_eq!(loaded, pca);
+        assert_eq!(loaded.transform(&images), pca.transform(&images));
+
+        let zca = Zca::fit(&images, 1e-2);
+        zca.save_npz(path("zca")).unwrap();
+        let loaded = Zca::load_npz(path("zca")).unwrap();
+        assert_eq!(loaded, zca);
+        assert_eq!(loaded.transform(&images), zca.transform(&images));
+
+        for name in &["gcn", "standardize", "pca", "zca"] {
+            std::fs::remove_file(path(name)).unwrap();
+        }
+    }
+}
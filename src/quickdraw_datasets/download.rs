@@ -0,0 +1,13 @@
+use crate::download_helper::downloader;
+
+const BASE_URL: &str = "https://storage.googleapis.com/quickdraw_dataset/full/numpy_bitmap";
+
+pub fn download(base_path: &str, categories: &[String]) -> Result<(), String> {
+    for category in categories {
+        let file_name = format!("{}.npy", category);
+        println!("Attempting to download {}...", file_name);
+        let url = format!("{}/{}", BASE_URL, file_name.replace(' ', "%20"));
+        downloader::download_file(base_path, url, &file_name)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,5 @@
+pub mod quickdraw_builder;
+pub use quickdraw_builder::quickdraw;
+
+#[cfg(feature = "download")]
+mod download;
@@ -0,0 +1,146 @@
+use ndarray::prelude::*;
+use ndarray::{Array2, Array3};
+
+use std::path::Path;
+
+#[cfg(feature = "download")]
+use super::download;
+use crate::image_folder;
+pub use crate::mnist_datasets::mnist_builder::Data;
+use crate::npy;
+
+static BASE_PATH: &str = "data/quickdraw/";
+static ROWS: usize = 28;
+static COLS: usize = 28;
+
+/// Configures which categories of the Google QuickDraw numpy bitmaps are loaded.
+///
+/// Every category is stored as `<category>.npy` holding `(n, 784)` `uint8` bitmaps. Label index
+/// `i` belongs to `categories[i]`. The first samples of every category form the training set,
+/// the following `test_fraction` of the (capped) samples the test set.
+pub struct QuickDraw {
+    categories: Vec<String>,
+    base_path: String,
+    max_per_category: Option<usize>,
+    test_fraction: f32,
+    normalized: bool,
+}
+
+impl QuickDraw {
+    pub fn new<S: AsRef<str>>(categories: &[S]) -> QuickDraw {
+        QuickDraw {
+            categories: categories.iter().map(|c| c.as_ref().to_string()).collect(),
+            base_path: BASE_PATH.to_string(),
+            max_per_category: None,
+            test_fraction: 0.1,
+            normalized: false,
+        }
+    }
+
+    /// Directory holding the `.npy` files, `data/quickdraw/` by default.
+    pub fn base_path(mut self, base_path: &str) -> QuickDraw {
+        self.base_path = base_path.to_string();
+        self
+    }
+
+    /// Use at most `max` samples of every category, split between training and test set.
+    pub fn max_per_category(mut self, max: usize) -> QuickDraw {
+        self.max_per_category = Some(max);
+        self
+    }
+
+    pub fn test_fraction(mut self, test_fraction: f32) -> QuickDraw {
+        assert!(
+            (0. ..=1.).contains(&test_fraction),
+            "Test fraction {} is not between 0 and 1.",
+            test_fraction
+        );
+        self.test_fraction = test_fraction;
+        self
+    }
+
+    pub fn normalized(mut self, normalized: bool) -> QuickDraw {
+        self.normalized = normalized;
+        self
+    }
+
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download(&self) -> Result<(), String> {
+        download::download(&self.base_path, &self.categories)
+    }
+
+    pub fn load(&self) -> Result<Data, String> {
+        let mut trn_img: Vec<u8> = Vec::new();
+        let mut trn_labels: Vec<usize> = Vec::new();
+        let mut tst_img: Vec<u8> = Vec::new();
+        let mut tst_labels: Vec<usize> = Vec::new();
+        for (label, category) in self.categories.iter().enumerate() {
+            let path = Path::new(&self.base_path).join(format!("{}.npy", category));
+            let bitmaps = npy::load_npy::<u8, _>(&path)?;
+            if bitmaps.ndim() != 2 || bitmaps.shape()[1] != ROWS * COLS {
+                return Err(format!(
+                    "Expected bitmaps of shape (n, {}) in {:?}, got {:?}.",
+                    ROWS * COLS,
+                    path,
+                    bitmaps.shape()
+                ));
+            }
+            let len = match self.max_per_category {
+                Some(max) => max.min(bitmaps.shape()[0]),
+                None => bitmaps.shape()[0],
+            };
+            let tst_len = (len as f32 * self.test_fraction).round() as usize;
+            let trn_len = len - tst_len;
+            let bitmaps = bitmaps.slice(s![..len, ..]);
+            trn_img.extend(bitmaps.slice(s![..trn_len, ..]).iter());
+            tst_img.extend(bitmaps.slice(s![trn_len.., ..]).iter());
+            trn_labels.extend(std::iter::repeat(label).take(trn_len));
+            tst_labels.extend(std::iter::repeat(label).take(tst_len));
+        }
+
+        let to_images = |img: Vec<u8>, len: usize| -> Array3<f32> {
+            let mut img = Array3::from_shape_vec((len, ROWS, COLS), img)
+                .unwrap()
+                .mapv(|x| x as f32);
+            if self.normalized {
+                img.mapv_inplace(|x| x / 256.);
+            }
+            img
+        };
+        let classes = self.categories.len();
+        let trn_lbl: Array2<f32> = image_folder::one_hot(&trn_labels, classes);
+        let tst_lbl: Array2<f32> = image_folder::one_hot(&tst_labels, classes);
+        Ok(Data {
+            trn_img: to_images(trn_img, trn_labels.len()),
+            trn_lbl,
+            tst_img: to_images(tst_img, tst_labels.len()),
+            tst_lbl,
+        })
+    }
+}
+
+pub mod quickdraw {
+    pub use super::{Data, QuickDraw};
+    pub fn new<S: AsRef<str>>(categories: &[S], max_per_category: usize) -> Data {
+        QuickDraw::new(categories)
+            .max_per_category(max_per_category)
+            .load()
+            .unwrap()
+    }
+    pub fn new_normalized<S: AsRef<str>>(categories: &[S], max_per_category: usize) -> Data {
+        QuickDraw::new(categories)
+            .max_per_category(max_per_category)
+            .normalized(true)
+            .load()
+            .unwrap()
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download_and_extract<S: AsRef<str>>(categories: &[S]) {
+        QuickDraw::new(categories).download().unwrap();
+    }
+}
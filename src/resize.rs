@@ -0,0 +1,274 @@
+//! Resizing, padding and cropping of image tensors.
+//!
+//! All functions act on the last two axes of an array, so the same call handles a single
+//! `(rows, cols)` image, the NHW images of `mnist_builder::Data` and the NCHW images of
+//! `cifar_builder::Data`. Images are processed in parallel.
+//!
+//! ```no_run
+//! use datasets::mnist;
+//! use datasets::resize::{pad, resize, Interpolation};
+//!
+//! let data = mnist::new_normalized();
+//! // LeNet-5 expects 32 x 32 inputs, either by padding or by upscaling the digits.
+//! let padded = pad(&data.trn_img, 2, 0.);
+//! let upscaled = resize(&data.trn_img, 32, 32, Interpolation::Bilinear);
+//! ```
+
+use ndarray::prelude::*;
+use rayon::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Value of the closest source pixel.
+    Nearest,
+    /// Linear interpolation of the 2 x 2 closest source pixels.
+    Bilinear,
+    /// Cubic convolution (Keys, a = -0.5) of the 4 x 4 closest source pixels.
+    Bicubic,
+    /// Average of the source pixels covered by the output pixel, weighted by the covered area.
+    /// Avoids aliasing when downscaling.
+    Area,
+}
+
+/// Source pixels and their weights contributing to every output pixel along one axis.
+type Weights = Vec<Vec<(usize, f32)>>;
+
+fn cubic(x: f32) -> f32 {
+    let a = -0.5;
+    let x = x.abs();
+    if x <= 1. {
+        ((a + 2.) * x - (a + 3.)) * x * x + 1.
+    } else if x < 2. {
+        ((x - 5.) * x + 8.) * x * a - 4. * a
+    } else {
+        0.
+    }
+}
+
+/// Interpolation weights for resampling an axis of length `src` to `dst`. Pixel centers are
+/// aligned like in PIL and OpenCV, taps outside of the image are clamped to the border.
+fn weights(src: usize, dst: usize, interpolation: Interpolation) -> Weights {
+    let scale = src as f32 / dst as f32;
+    let clamp = |i: isize| i.max(0).min(src as isize - 1) as usize;
+    (0..dst)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * scale - 0.5;
+            match interpolation {
+                Interpolation::Nearest => {
+                    vec![(clamp(((i as f32 + 0.5) * scale).floor() as isize), 1.)]
+                }
+                Interpolation::Bilinear => {
+                    let left = center.floor();
+                    let t = center - left;
+                    let left = left as isize;
+                    vec![(clamp(left), 1. - t), (clamp(left + 1), t)]
+                }
+                Interpolation::Bicubic => {
+                    let left = center.floor();
+                    let t = center - left;
+                    let left = left as isize;
+                    (-1..3)
+                        .map(|k| (clamp(left + k), cubic(t - k as f32)))
+                        .collect()
+                }
+                Interpolation::Area => {
+                    let (start, end) = (i as f32 * scale, (i + 1) as f32 * scale);
+                    (start.floor() as usize..(end.ceil() as usize).min(src))
+                        .map(|j| {
+                            let overlap = end.min(j as f32 + 1.) - start.max(j as f32);
+                            (j, overlap / scale)
+                        })
+                        .filter(|&(_, w)| w > 0.)
+                        .collect()
+                }
+            }
+        })
+        .collect()
+}
+
+/// Resample a single channel with separable row and column weights.
+fn resample(
+    channel: &ArrayView2<f32>,
+    row_weights: &Weights,
+    col_weights: &Weights,
+) -> Array2<f32> {
+    let rows: Array2<f32> =
+        Array2::from_shape_fn((row_weights.len(), channel.ncols()), |(r, c)| {
+            row_weights[r]
+                .iter()
+                .map(|&(j, w)| w * channel[[j, c]])
+                .sum()
+        });
+    Array2::from_shape_fn((row_weights.len(), col_weights.len()), |(r, c)| {
+        col_weights[c].iter().map(|&(j, w)| w * rows[[r, j]]).sum()
+    })
+}
+
+/// Apply `f` to every `(rows, cols)` image of `images` in parallel and stack the results, which
+/// all have to be of shape `out_rows` x `out_cols`.
+fn map_images<D, F>(images: &Array<f32, D>, out_rows: usize, out_cols: usize, f: F) -> Array<f32, D>
+where
+    D: Dimension,
+    F: Fn(ArrayView2<f32>) -> Array2<f32> + Sync,
+{
+    let shape = images.shape();
+    let ndim = shape.len();
+    assert!(ndim >= 2, "Images need at least a row and a column axis.");
+    let (rows, cols) = (shape[ndim - 2], shape[ndim - 1]);
+    let len = shape[..ndim - 2].iter().product();
+    let standard = images.as_standard_layout();
+    let flat = standard.view().into_shape((len, rows, cols)).unwrap();
+    let outputs: Vec<Array2<f32>> = (0..len)
+        .into_par_iter()
+        .map(|i| f(flat.index_axis(Axis(0), i)))
+        .collect();
+    let mut out: Array3<f32> = Array3::zeros((len, out_rows, out_cols));
+    for (mut target, image) in out.outer_iter_mut().zip(outputs) {
+        target.assign(&image);
+    }
+    let mut dim = images.raw_dim();
+    dim[ndim - 2] = out_rows;
+    dim[ndim - 1] = out_cols;
+    out.into_shape(dim).unwrap()
+}
+
+/// Resize every image to `rows` x `cols`.
+pub fn resize<D: Dimension>(
+    images: &Array<f32, D>,
+    rows: usize,
+    cols: usize,
+    interpolation: Interpolation,
+) -> Array<f32, D> {
+    let shape = images.shape();
+    let ndim = shape.len();
+    assert!(ndim >= 2, "Images need at least a row and a column axis.");
+    let (src_rows, src_cols) = (shape[ndim - 2], shape[ndim - 1]);
+    assert!(
+        src_rows > 0 && src_cols > 0 || rows * cols == 0,
+        "Can't resize empty images to {} x {}.",
+        rows,
+        cols
+    );
+    let row_weights = weights(src_rows, rows, interpolation);
+    let col_weights = weights(src_cols, cols, interpolation);
+    map_images(images, rows, cols, |image| {
+        resample(&image, &row_weights, &col_weights)
+    })
+}
+
+/// Add `padding` pixels of value `value` on every side of every image.
+pub fn pad<D: Dimension>(images: &Array<f32, D>, padding: usize, value: f32) -> Array<f32, D> {
+    let shape = images.shape();
+    let ndim = shape.len();
+    assert!(ndim >= 2, "Images need at least a row and a column axis.");
+    let (rows, cols) = (shape[ndim - 2] + 2 * padding, shape[ndim - 1] + 2 * padding);
+    map_images(images, rows, cols, |image| {
+        let mut padded = Array2::from_elem((rows, cols), value);
+        padded
+            .slice_mut(s![padding..rows - padding, padding..cols - padding])
+            .assign(&image);
+        padded
+    })
+}
+
+/// Cut the central `rows` x `cols` window out of every image. If the size difference is odd,
+/// the window is shifted towards the top left.
+pub fn center_crop<D: Dimension>(
+    images: &Array<f32, D>,
+    rows: usize,
+    cols: usize,
+) -> Array<f32, D> {
+    let shape = images.shape();
+    let ndim = shape.len();
+    assert!(ndim >= 2, "Images need at least a row and a column axis.");
+    let (src_rows, src_cols) = (shape[ndim - 2], shape[ndim - 1]);
+    assert!(
+        rows <= src_rows && cols <= src_cols,
+        "Can't crop {} x {} images to {} x {}.",
+        src_rows,
+        src_cols,
+        rows,
+        cols
+    );
+    let (top, left) = ((src_rows - rows) / 2, (src_cols - cols) / 2);
+    map_images(images, rows, cols, |image| {
+        image
+            .slice(s![top..top + rows, left..left + cols])
+            .to_owned()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERPOLATIONS: [Interpolation; 4] = [
+        Interpolation::Nearest,
+        Interpolation::Bilinear,
+        Interpolation::Bicubic,
+        Interpolation::Area,
+    ];
+
+    #[test]
+    fn constant_images_stay_constant() {
+        let images = Array4::from_shape_fn((2, 3, 6, 9), |(n, c, _, _)| (n * 3 + c) as f32);
+        for &interpolation in &INTERPOLATIONS {
+            for &(rows, cols) in &[(3, 4), (6, 9), (13, 20), (1, 1)] {
+                let resized = resize(&images, rows, cols, interpolation);
+                assert_eq!(resized.dim(), (2, 3, rows, cols));
+                for ((n, c, _, _), &x) in resized.indexed_iter() {
+                    let expected = (n * 3 + c) as f32;
+                    assert!(
+                        (x - expected).abs() < 1e-5 * expected.max(1.),
+                        "{:?}",
+                        interpolation
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn area_downscaling_averages_blocks() {
+        let image = Array2::from_shape_fn((4, 6), |(r, c)| (r * 6 + c) as f32);
+        let downscaled = resize(&image, 2, 3, Interpolation::Area);
+        let expected = Array2::from_shape_fn((2, 3), |(r, c)| {
+            image
+                .slice(s![2 * r..2 * r + 2, 2 * c..2 * c + 2])
+                .mean()
+                .unwrap()
+        });
+        assert!(downscaled
+            .iter()
+            .zip(&expected)
+            .all(|(a, b)| (a - b).abs() < 1e-5));
+    }
+
+    #[test]
+    fn nearest_upscaling_repeats_pixels() {
+        let image = arr2(&[[1., 2.], [3., 4.]]);
+        assert_eq!(
+            resize(&image, 4, 4, Interpolation::Nearest),
+            arr2(&[
+                [1., 1., 2., 2.],
+                [1., 1., 2., 2.],
+                [3., 3., 4., 4.],
+                [3., 3., 4., 4.]
+            ])
+        );
+    }
+
+    #[test]
+    fn pad_and_center_crop() {
+        let images = Array3::from_shape_fn((2, 5, 4), |(n, r, c)| (n * 20 + r * 4 + c) as f32);
+        let padded = pad(&images, 2, -1.);
+        assert_eq!(padded.dim(), (2, 9, 8));
+        assert_eq!(padded[[1, 0, 0]], -1.);
+        assert_eq!(padded[[1, 8, 7]], -1.);
+        assert_eq!(padded[[1, 2, 2]], images[[1, 0, 0]]);
+        assert_eq!(center_crop(&padded, 5, 4), images);
+        assert_eq!(pad(&images, 0, -1.), images);
+        // Odd differences shift the window towards the top left.
+        assert_eq!(center_crop(&images, 2, 1), images.slice(s![.., 1..3, 1..2]));
+    }
+}
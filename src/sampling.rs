@@ -0,0 +1,46 @@
+//! Samplers for the distributions not provided by `rand` itself.
+
+use rand::Rng;
+
+/// Sample from N(0, 1) with the Box-Muller transform.
+pub(crate) fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    // 1 - u lies in (0, 1], so its logarithm is finite.
+    let u: f64 = 1. - rng.gen::<f64>();
+    let v: f64 = rng.gen();
+    (-2. * u.ln()).sqrt() * (2. * std::f64::consts::PI * v).cos()
+}
+
+/// Sample from Gamma(shape, 1) with the method of Marsaglia and Tsang.
+pub(crate) fn gamma<R: Rng + ?Sized>(rng: &mut R, shape: f64) -> f64 {
+    assert!(shape > 0., "Gamma shape has to be positive, got {}.", shape);
+    if shape < 1. {
+        // Gamma(a) = Gamma(a + 1) * U^(1 / a)
+        let u: f64 = 1. - rng.gen::<f64>();
+        return gamma(rng, shape + 1.) * u.powf(1. / shape);
+    }
+    let d = shape - 1. / 3.;
+    let c = 1. / (9. * d).sqrt();
+    loop {
+        let x = standard_normal(rng);
+        let v = (1. + c * x).powi(3);
+        if v <= 0. {
+            continue;
+        }
+        let u: f64 = 1. - rng.gen::<f64>();
+        if u.ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+            return d * v;
+        }
+    }
+}
+
+/// Sample from Beta(a, b).
+pub(crate) fn beta<R: Rng + ?Sized>(rng: &mut R, a: f64, b: f64) -> f64 {
+    let x = gamma(rng, a);
+    let y = gamma(rng, b);
+    if x + y == 0. {
+        // Both samples underflowed, which happens for tiny shapes where Beta(a, b) is
+        // concentrated at 0 and 1.
+        return (a / (a + b) > rng.gen::<f64>()) as u8 as f64;
+    }
+    x / (x + y)
+}
@@ -0,0 +1,23 @@
+use std::path::Path;
+
+use crate::download_helper::downloader;
+
+const BASE_URL: &str = "http://download.tensorflow.org/data";
+const ARCHIVE: &str = "speech_commands_v0.02.tar.gz";
+
+/// The tarball has no top level folder, so it is extracted directly into `base_path`.
+pub fn download_and_extract(base_path: &str) -> Result<(), String> {
+    println!("Attempting to download and extract {}...", ARCHIVE);
+    downloader::download(base_path, BASE_URL.to_string(), vec![ARCHIVE])?;
+    let base_dir = Path::new(base_path);
+    if base_dir.join("testing_list.txt").exists() {
+        println!(
+            "  Dataset already extracted to {:?}, skipping extraction.",
+            base_dir
+        );
+        return Ok(());
+    }
+    downloader::extract_tar_gz(&base_dir.join(ARCHIVE), base_dir)?;
+    println!("done unpacking .tar.gz");
+    Ok(())
+}
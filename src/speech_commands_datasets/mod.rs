@@ -0,0 +1,6 @@
+pub mod speech_commands_builder;
+pub use speech_commands_builder::speech_commands;
+
+#[cfg(feature = "download")]
+mod download;
+mod wav;
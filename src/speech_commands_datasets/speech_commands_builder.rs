@@ -0,0 +1,134 @@
+use ndarray::prelude::*;
+use rayon::prelude::*;
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "download")]
+use super::download;
+use super::wav;
+
+static VALIDATION_LIST: &str = "validation_list.txt";
+static TESTING_LIST: &str = "testing_list.txt";
+static SAMPLE_RATE: u32 = 16_000;
+
+/// One second clips of spoken words, sampled at 16 kHz and scaled to [-1, 1).
+///
+/// Every row of `*_wav` holds one clip, shorter recordings are padded with silence at the end.
+/// `*_lbl[i]` is the index of the spoken word in `class_names`, the sorted folder names.
+pub struct Data {
+    pub trn_wav: Array2<f32>,
+    pub trn_lbl: Array1<usize>,
+    pub val_wav: Array2<f32>,
+    pub val_lbl: Array1<usize>,
+    pub tst_wav: Array2<f32>,
+    pub tst_lbl: Array1<usize>,
+    pub class_names: Vec<String>,
+}
+
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap_or_else(|_| panic!("Unable to read directory {:?}.", dir))
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Relative paths like `right/bb05582b_nohash_0.wav` listed in `file`.
+fn read_list(path: &Path) -> HashSet<String> {
+    fs::read_to_string(path)
+        .unwrap_or_else(|_| panic!("Unable to read {:?}.", path))
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Decode all clips in parallel and pad or crop them to `len` samples.
+fn decode_clips(paths: &[PathBuf], len: usize) -> Array2<f32> {
+    let clips: Vec<Vec<f32>> = paths
+        .par_iter()
+        .map(|path| {
+            let bytes = fs::read(path).unwrap_or_else(|_| panic!("Unable to read {:?}.", path));
+            let (samples, sample_rate) = wav::decode(&bytes)
+                .unwrap_or_else(|e| panic!("Failed to decode {:?}: {}", path, e));
+            assert!(
+                sample_rate == SAMPLE_RATE,
+                "Expected a sample rate of {} got {} in {:?}.",
+                SAMPLE_RATE,
+                sample_rate,
+                path
+            );
+            samples
+        })
+        .collect();
+    let mut wav: Array2<f32> = Array2::zeros((clips.len(), len));
+    for (mut row, clip) in wav.outer_iter_mut().zip(clips) {
+        for (x, sample) in row.iter_mut().zip(clip) {
+            *x = sample;
+        }
+    }
+    wav
+}
+
+fn get_data(base_path: &str) -> Data {
+    let base_path = Path::new(base_path);
+    let validation = read_list(&base_path.join(VALIDATION_LIST));
+    let testing = read_list(&base_path.join(TESTING_LIST));
+
+    // Every word has its own folder, `_background_noise_` holds longer noise recordings.
+    let class_dirs: Vec<PathBuf> = sorted_entries(base_path)
+        .into_iter()
+        .filter(|path| path.is_dir())
+        .filter(|path| !path.file_name().unwrap().to_string_lossy().starts_with('_'))
+        .collect();
+    let class_names: Vec<String> = class_dirs
+        .iter()
+        .map(|dir| dir.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+
+    let mut splits: [(Vec<PathBuf>, Vec<usize>); 3] = Default::default();
+    for (label, (dir, name)) in class_dirs.iter().zip(&class_names).enumerate() {
+        for path in sorted_entries(dir) {
+            if path.extension().map_or(true, |ext| ext != "wav") {
+                continue;
+            }
+            let key = format!("{}/{}", name, path.file_name().unwrap().to_string_lossy());
+            let split = if validation.contains(&key) {
+                1
+            } else if testing.contains(&key) {
+                2
+            } else {
+                0
+            };
+            splits[split].0.push(path);
+            splits[split].1.push(label);
+        }
+    }
+    let [trn, val, tst] = splits;
+    let len = SAMPLE_RATE as usize;
+    Data {
+        trn_wav: decode_clips(&trn.0, len),
+        trn_lbl: Array1::from(trn.1),
+        val_wav: decode_clips(&val.0, len),
+        val_lbl: Array1::from(val.1),
+        tst_wav: decode_clips(&tst.0, len),
+        tst_lbl: Array1::from(tst.1),
+        class_names,
+    }
+}
+
+pub mod speech_commands {
+    pub use super::Data;
+    static BASE_PATH: &str = "data/speech_commands_v0.02/";
+    pub fn new() -> Data {
+        super::get_data(BASE_PATH)
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download_and_extract() {
+        super::download::download_and_extract(BASE_PATH).unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use std::io::Read;
+
+/// Samples of the first channel of a RIFF/WAVE file with 8, 16, 24 or 32 bit integer PCM data,
+/// scaled to [-1, 1), together with the sample rate.
+pub fn decode(bytes: &[u8]) -> Result<(Vec<f32>, u32), String> {
+    let mut reader = bytes;
+    let mut tag = [0u8; 4];
+    let mut read_tag = |reader: &mut &[u8]| -> Result<[u8; 4], String> {
+        reader
+            .read_exact(&mut tag)
+            .map_err(|e| format!("Unexpected end of file: {:?}", e))?;
+        Ok(tag)
+    };
+    let read_u32 = |reader: &mut &[u8]| {
+        reader
+            .read_u32::<LittleEndian>()
+            .map_err(|e| format!("Unexpected end of file: {:?}", e))
+    };
+    if &read_tag(&mut reader)? != b"RIFF" {
+        return Err("Missing RIFF header.".to_string());
+    }
+    read_u32(&mut reader)?;
+    if &read_tag(&mut reader)? != b"WAVE" {
+        return Err("Not a WAVE file.".to_string());
+    }
+
+    // (channels, sample rate, bits per sample) of the `fmt ` chunk.
+    let mut format: Option<(usize, u32, usize)> = None;
+    loop {
+        let id = read_tag(&mut reader)?;
+        let size = read_u32(&mut reader)? as usize;
+        if reader.len() < size {
+            return Err(format!(
+                "Chunk {:?} is truncated.",
+                String::from_utf8_lossy(&id)
+            ));
+        }
+        let (chunk, rest) = reader.split_at(size);
+        // Chunks are padded to an even number of bytes.
+        reader = rest.get(size % 2..).unwrap_or(&[]);
+        match &id {
+            b"fmt " => {
+                let mut chunk = chunk;
+                let field = |e: std::io::Error| format!("Malformed fmt chunk: {:?}", e);
+                let audio_format = chunk.read_u16::<LittleEndian>().map_err(field)?;
+                let channels = chunk.read_u16::<LittleEndian>().map_err(field)? as usize;
+                let sample_rate = chunk.read_u32::<LittleEndian>().map_err(field)?;
+                chunk.read_u32::<LittleEndian>().map_err(field)?;
+                chunk.read_u16::<LittleEndian>().map_err(field)?;
+                let bits = chunk.read_u16::<LittleEndian>().map_err(field)? as usize;
+                if audio_format != 1 {
+                    return Err(format!(
+                        "Unsupported audio format {}, expected PCM.",
+                        audio_format
+                    ));
+                }
+                if channels == 0 || ![8, 16, 24, 32].contains(&bits) {
+                    return Err(format!(
+                        "Unsupported PCM data with {} channels and {} bits per sample.",
+                        channels, bits
+                    ));
+                }
+                format = Some((channels, sample_rate, bits));
+            }
+            b"data" => {
+                let (channels, sample_rate, bits) =
+                    format.ok_or_else(|| "Data chunk before fmt chunk.".to_string())?;
+                let frame = channels * bits / 8;
+                let samples = chunk
+                    .chunks_exact(frame)
+                    .map(|frame| match bits {
+                        8 => (frame[0] as f32 - 128.) / 128.,
+                        16 => i16::from_le_bytes([frame[0], frame[1]]) as f32 / 32768.,
+                        24 => {
+                            i32::from_le_bytes([0, frame[0], frame[1], frame[2]]) as f32
+                                / 2_147_483_648.
+                        }
+                        _ => {
+                            i32::from_le_bytes([frame[0], frame[1], frame[2], frame[3]]) as f32
+                                / 2_147_483_648.
+                        }
+                    })
+                    .collect();
+                return Ok((samples, sample_rate));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A WAVE file with a PCM `fmt ` chunk, the `extra` chunks and a `data` chunk.
+    fn wav(channels: u16, bits: u16, extra: &[(&[u8; 4], &[u8])], data: &[u8]) -> Vec<u8> {
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&1u16.to_le_bytes());
+        fmt.extend_from_slice(&channels.to_le_bytes());
+        fmt.extend_from_slice(&16_000u32.to_le_bytes());
+        fmt.extend_from_slice(&(16_000 * (channels * bits / 8) as u32).to_le_bytes());
+        fmt.extend_from_slice(&(channels * bits / 8).to_le_bytes());
+        fmt.extend_from_slice(&bits.to_le_bytes());
+
+        let mut chunks = Vec::new();
+        let mut add = |id: &[u8; 4], content: &[u8]| {
+            chunks.extend_from_slice(id);
+            chunks.extend_from_slice(&(content.len() as u32).to_le_bytes());
+            chunks.extend_from_slice(content);
+            if content.len() % 2 == 1 {
+                chunks.push(0);
+            }
+        };
+        add(b"fmt ", &fmt);
+        for (id, content) in extra {
+            add(id, content);
+        }
+        add(b"data", data);
+
+        let mut bytes = b"RIFF".to_vec();
+        bytes.extend_from_slice(&(chunks.len() as u32 + 4).to_le_bytes());
+        bytes.extend_from_slice(b"WAVE");
+        bytes.extend(chunks);
+        bytes
+    }
+
+    fn le16(samples: &[i16]) -> Vec<u8> {
+        samples
+            .iter()
+            .flat_map(|x| x.to_le_bytes().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn mono_16_bit() {
+        let bytes = wav(1, 16, &[], &le16(&[0, 16384, -32768, 32767]));
+        let (samples, sample_rate) = decode(&bytes).unwrap();
+        assert_eq!(sample_rate, 16_000);
+        assert_eq!(samples, vec![0., 0.5, -1., 32767. / 32768.]);
+    }
+
+    #[test]
+    fn skips_unknown_chunks() {
+        // An odd-sized LIST chunk is followed by a padding byte.
+        let extra: [(&[u8; 4], &[u8]); 2] = [(b"LIST", b"INFOabc"), (b"fact", &[1, 0, 0, 0])];
+        let bytes = wav(1, 16, &extra, &le16(&[-16384, 8192]));
+        assert_eq!(decode(&bytes).unwrap().0, vec![-0.5, 0.25]);
+    }
+
+    #[test]
+    fn other_sample_sizes_keep_the_first_channel() {
+        let (samples, _) = decode(&wav(1, 8, &[], &[0, 128, 192])).unwrap();
+        assert_eq!(samples, vec![-1., 0., 0.5]);
+
+        let data = [0x00, 0x00, 0x40, 0x00, 0x00, 0x80];
+        let (samples, _) = decode(&wav(1, 24, &[], &data)).unwrap();
+        assert_eq!(samples, vec![0.5, -1.]);
+
+        // Stereo frames of (left, right), only the left channel is returned.
+        let data: Vec<u8> = [i32::MIN, 7, 1 << 30, 7]
+            .iter()
+            .flat_map(|x| x.to_le_bytes().to_vec())
+            .collect();
+        let (samples, _) = decode(&wav(2, 32, &[], &data)).unwrap();
+        assert_eq!(samples, vec![-1., 0.5]);
+    }
+
+    #[test]
+    fn malformed_files() {
+        let valid = wav(1, 16, &[], &le16(&[1, 2]));
+        assert!(decode(&valid[..valid.len() - 1]).is_err());
+        assert!(decode(&valid[..12]).is_err());
+        assert!(decode(b"RIFX\x00\x00\x00\x00WAVE").is_err());
+        assert!(decode(&wav(1, 12, &[], &[])).is_err());
+        assert!(decode(&wav(0, 16, &[], &[])).is_err());
+
+        let mut float = valid.clone();
+        float[20] = 3;
+        assert!(decode(&float).is_err());
+
+        // A data chunk without a preceding fmt chunk.
+        let mut no_fmt = b"RIFF\x0c\x00\x00\x00WAVEdata\x00\x00\x00\x00".to_vec();
+        assert!(decode(&no_fmt).is_err());
+        no_fmt.truncate(12);
+        assert!(decode(&no_fmt).is_err());
+    }
+}
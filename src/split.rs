@@ -0,0 +1,320 @@
+//! Stratified validation splits and k-fold cross-validation.
+//!
+//! Splits are computed on the labels alone and returned as sorted sample indices, every class
+//! is split in the same proportions. `dataset::Dataset::train_val` turns indices into a dataset
+//! with the validation samples as test set.
+//!
+//! ```no_run
+//! use datasets::dataset::Dataset;
+//! use datasets::mnist;
+//! use datasets::split::{stratified_split, StratifiedKFold};
+//!
+//! let data = mnist::new_normalized();
+//! let (trn, val) = stratified_split(&data.trn_lbl, 0.1, 0);
+//! let holdout = data.train_val(&trn, &val);
+//! assert_eq!(holdout.tst_img.shape()[0], 6_000);
+//!
+//! for (trn, val) in StratifiedKFold::new(&data.trn_lbl, 5, 0) {
+//!     let fold = data.train_val(&trn, &val);
+//! }
+//! ```
+
+use ndarray::prelude::*;
+use ndarray::{Data, RemoveAxis};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+/// Class labels of a dataset, either one-hot rows or class indices.
+pub trait Labels {
+    /// Class index of every sample. One-hot (or soft) labels map to their largest entry, rows
+    /// without a label to class 0.
+    fn classes(&self) -> Vec<usize>;
+}
+
+impl<S: Data<Elem = f32>> Labels for ArrayBase<S, Ix2> {
+    fn classes(&self) -> Vec<usize> {
+        self.outer_iter()
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .fold((0, f32::NEG_INFINITY), |best, (i, &x)| {
+                        if x > best.1 {
+                            (i, x)
+                        } else {
+                            best
+                        }
+                    })
+                    .0
+            })
+            .collect()
+    }
+}
+
+impl<S: Data<Elem = usize>> Labels for ArrayBase<S, Ix1> {
+    fn classes(&self) -> Vec<usize> {
+        self.to_vec()
+    }
+}
+
+impl Labels for [usize] {
+    fn classes(&self) -> Vec<usize> {
+        self.to_vec()
+    }
+}
+
+impl Labels for Vec<usize> {
+    fn classes(&self) -> Vec<usize> {
+        self.clone()
+    }
+}
+
+/// Sample indices of every class, shuffled with `rng`.
+pub(crate) fn shuffled_classes(classes: &[usize], rng: &mut StdRng) -> Vec<Vec<usize>> {
+    let num_classes = classes.iter().max().map_or(0, |&c| c + 1);
+    let mut members = vec![Vec::new(); num_classes];
+    for (i, &class) in classes.iter().enumerate() {
+        members[class].push(i);
+    }
+    for indices in members.iter_mut() {
+        indices.shuffle(rng);
+    }
+    members
+}
+
+/// Split the samples into training and validation indices, putting a fraction of
+/// `val_fraction` of every class into the validation set.
+///
+/// The validation set has `round(val_fraction * n)` samples, the per-class shares are rounded
+/// by largest remainder. Both index lists are sorted.
+pub fn stratified_split<L: Labels + ?Sized>(
+    labels: &L,
+    val_fraction: f64,
+    seed: u64,
+) -> (Vec<usize>, Vec<usize>) {
+    assert!(
+        (0. ..=1.).contains(&val_fraction),
+        "Validation fraction has to be in [0, 1], got {}.",
+        val_fraction
+    );
+    let classes = labels.classes();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let members = shuffled_classes(&classes, &mut rng);
+
+    let exact: Vec<f64> = members
+        .iter()
+        .map(|m| m.len() as f64 * val_fraction)
+        .collect();
+    let mut counts: Vec<usize> = exact.iter().map(|x| x.floor() as usize).collect();
+    let target = (classes.len() as f64 * val_fraction).round() as usize;
+    let mut by_remainder: Vec<usize> = (0..members.len()).collect();
+    by_remainder.sort_by(|&a, &b| {
+        let (ra, rb) = (exact[a] - exact[a].floor(), exact[b] - exact[b].floor());
+        rb.total_cmp(&ra).then(a.cmp(&b))
+    });
+    let missing = target.saturating_sub(counts.iter().sum());
+    for &class in by_remainder.iter().take(missing) {
+        counts[class] += 1;
+    }
+
+    let mut trn = Vec::with_capacity(classes.len() - target);
+    let mut val = Vec::with_capacity(target);
+    for (indices, &count) in members.iter().zip(&counts) {
+        val.extend(&indices[..count]);
+        trn.extend(&indices[count..]);
+    }
+    trn.sort_unstable();
+    val.sort_unstable();
+    (trn, val)
+}
+
+/// Iterates over `k` folds of `(train, validation)` indices, every sample is in exactly one
+/// validation set. Each class is spread evenly over the folds and fold sizes differ by at
+/// most one sample.
+pub struct StratifiedKFold {
+    folds: Vec<Vec<usize>>,
+    next: usize,
+}
+
+impl StratifiedKFold {
+    pub fn new<L: Labels + ?Sized>(labels: &L, k: usize, seed: u64) -> StratifiedKFold {
+        assert!(
+            k >= 2,
+            "Cross-validation needs at least 2 folds, got {}.",
+            k
+        );
+        let classes = labels.classes();
+        assert!(
+            k <= classes.len(),
+            "Can't split {} samples into {} folds.",
+            classes.len(),
+            k
+        );
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut folds = vec![Vec::new(); k];
+        // Deal the samples class by class, continuing where the previous class stopped.
+        let mut position = 0;
+        for indices in shuffled_classes(&classes, &mut rng) {
+            for index in indices {
+                folds[position % k].push(index);
+                position += 1;
+            }
+        }
+        for fold in folds.iter_mut() {
+            fold.sort_unstable();
+        }
+        StratifiedKFold { folds, next: 0 }
+    }
+
+    /// Validation indices of every fold.
+    pub fn folds(&self) -> &[Vec<usize>] {
+        &self.folds
+    }
+}
+
+impl Iterator for StratifiedKFold {
+    type Item = (Vec<usize>, Vec<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let val = self.folds.get(self.next)?.clone();
+        let mut trn: Vec<usize> = self
+            .folds
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != self.next)
+            .flat_map(|(_, fold)| fold.iter().cloned())
+            .collect();
+        trn.sort_unstable();
+        self.next += 1;
+        Some((trn, val))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.folds.len() - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for StratifiedKFold {}
+
+/// Copy the images and labels of the samples at `indices`.
+pub fn select<D: Dimension + RemoveAxis>(
+    images: &Array<f32, D>,
+    labels: &Array2<f32>,
+    indices: &[usize],
+) -> (Array<f32, D>, Array2<f32>) {
+    (
+        images.select(Axis(0), indices),
+        labels.select(Axis(0), indices),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 30 samples of class 0, 50 of class 1 and 20 of class 2, interleaved.
+    fn labels() -> Vec<usize> {
+        (0..100)
+            .map(|i| match i % 10 {
+                0..=2 => 0,
+                3..=7 => 1,
+                _ => 2,
+            })
+            .collect()
+    }
+
+    fn assert_partition(trn: &[usize], val: &[usize], n: usize) {
+        let mut all: Vec<usize> = trn.iter().chain(val).cloned().collect();
+        all.sort_unstable();
+        assert_eq!(all, (0..n).collect::<Vec<usize>>());
+        assert!(trn.windows(2).all(|w| w[0] < w[1]));
+        assert!(val.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn one_hot_and_index_labels() {
+        let one_hot = arr2(&[[0f32, 1., 0.], [0.2, 0.1, 0.7], [1., 0., 0.]]);
+        assert_eq!(one_hot.classes(), vec![1, 2, 0]);
+        assert_eq!(one_hot.view().classes(), vec![1, 2, 0]);
+        assert_eq!(Array1::from(vec![2usize, 0]).classes(), vec![2, 0]);
+        assert_eq!([1usize, 1][..].classes(), vec![1, 1]);
+    }
+
+    #[test]
+    fn stratified_proportions() {
+        let labels = labels();
+        let (trn, val) = stratified_split(&labels, 0.2, 3);
+        assert_partition(&trn, &val, labels.len());
+        let mut counts = [0; 3];
+        for &i in &val {
+            counts[labels[i]] += 1;
+        }
+        assert_eq!(counts, [6, 10, 4]);
+        assert_eq!(stratified_split(&labels, 0., 3).1, Vec::<usize>::new());
+        assert_eq!(stratified_split(&labels, 1., 3).0, Vec::<usize>::new());
+    }
+
+    #[test]
+    fn largest_remainder_rounding() {
+        // Exact shares are 0.9, 1.5 and 0.6 samples, the largest remainders go to classes 0
+        // and 2, so every class gives one of the three validation samples.
+        let labels = vec![0usize, 0, 0, 1, 1, 1, 1, 1, 2, 2];
+        for seed in 0..20 {
+            let (trn, val) = stratified_split(&labels, 0.3, seed);
+            assert_partition(&trn, &val, labels.len());
+            let classes: Vec<usize> = val.iter().map(|&i| labels[i]).collect();
+            assert_eq!(classes, vec![0, 1, 2]);
+        }
+        assert_eq!(stratified_split(&labels, 0.3, 1).1, vec![0, 3, 9]);
+    }
+
+    #[test]
+    fn deterministic_by_seed() {
+        let labels = labels();
+        assert_eq!(
+            stratified_split(&labels, 0.25, 9),
+            stratified_split(&labels, 0.25, 9)
+        );
+        assert_ne!(
+            stratified_split(&labels, 0.25, 9),
+            stratified_split(&labels, 0.25, 10)
+        );
+        let folds = |seed| StratifiedKFold::new(&labels, 3, seed).folds().to_vec();
+        assert_eq!(folds(4), folds(4));
+        assert_ne!(folds(4), folds(5));
+    }
+
+    #[test]
+    #[should_panic(expected = "Validation fraction")]
+    fn rejects_nan_fraction() {
+        stratified_split(&labels(), f64::NAN, 0);
+    }
+
+    #[test]
+    fn k_fold() {
+        let labels = labels();
+        for &k in &[2, 3, 7] {
+            let folds = StratifiedKFold::new(&labels, k, 0);
+            assert_eq!(folds.len(), k);
+            let sizes: Vec<usize> = folds.folds().iter().map(|f| f.len()).collect();
+            let (min, max) = (sizes.iter().min().unwrap(), sizes.iter().max().unwrap());
+            assert!(max - min <= 1, "{:?}", sizes);
+
+            let mut seen = vec![0; labels.len()];
+            for (trn, val) in folds {
+                assert_partition(&trn, &val, labels.len());
+                // Every class is spread evenly over the folds.
+                for class in 0..3 {
+                    let total = labels.iter().filter(|&&c| c == class).count();
+                    let count = val.iter().filter(|&&i| labels[i] == class).count();
+                    assert!(count == total / k || count == total / k + 1);
+                }
+                for &i in &val {
+                    seen[i] += 1;
+                }
+            }
+            assert!(seen.iter().all(|&s| s == 1));
+        }
+    }
+}
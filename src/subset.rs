@@ -0,0 +1,147 @@
+//! Subsets by class, by predicate and by per-class caps.
+//!
+//! The functions return sorted sample indices and work with one-hot or index labels (see
+//! `split::Labels`). `dataset::Dataset` wraps them as `select_classes`, `filter`,
+//! `cap_per_class` and `cap_classes`.
+//!
+//! ```no_run
+//! use datasets::dataset::Dataset;
+//! use datasets::mnist;
+//!
+//! let data = mnist::new_normalized();
+//! // Binary 0 vs 1 problem with two label columns.
+//! let binary = data.select_classes(&[0, 1], true);
+//! assert_eq!(binary.trn_lbl.shape()[1], 2);
+//! // 100 labeled training digits per class, the test set stays complete.
+//! let few_labels = data.cap_per_class(100, 0);
+//! // Only digits with enough ink.
+//! let bold = data.filter(|image, _| image.sum() > 150.);
+//! ```
+
+use ndarray::prelude::*;
+use ndarray::RemoveAxis;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::split::{shuffled_classes, Labels};
+
+/// Number of samples of every class up to the largest class present.
+pub fn class_counts<L: Labels + ?Sized>(labels: &L) -> Vec<usize> {
+    let mut counts = Vec::new();
+    for class in labels.classes() {
+        if class >= counts.len() {
+            counts.resize(class + 1, 0);
+        }
+        counts[class] += 1;
+    }
+    counts
+}
+
+/// Indices of the samples belonging to one of `classes`.
+pub fn class_indices<L: Labels + ?Sized>(labels: &L, classes: &[usize]) -> Vec<usize> {
+    labels
+        .classes()
+        .iter()
+        .enumerate()
+        .filter(|(_, class)| classes.contains(class))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Indices of the samples for which `predicate(image, label)` holds.
+pub fn filter_indices<D, F>(
+    images: &Array<f32, D>,
+    labels: &Array2<f32>,
+    predicate: F,
+) -> Vec<usize>
+where
+    D: Dimension + RemoveAxis,
+    F: Fn(ArrayView<f32, D::Smaller>, ArrayView1<f32>) -> bool,
+{
+    images
+        .outer_iter()
+        .zip(labels.outer_iter())
+        .enumerate()
+        .filter(|(_, (image, label))| predicate(image.view(), label.view()))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Indices of at most `caps[c]` random samples of every class `c`, classes without a cap are
+/// dropped. Classes with fewer samples are kept completely.
+pub fn capped_indices<L: Labels + ?Sized>(labels: &L, caps: &[usize], seed: u64) -> Vec<usize> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut indices: Vec<usize> = shuffled_classes(&labels.classes(), &mut rng)
+        .into_iter()
+        .zip(caps)
+        .flat_map(|(members, &cap)| members.into_iter().take(cap))
+        .collect();
+    indices.sort_unstable();
+    indices
+}
+
+/// Keep the label columns of `classes` in the given order, so that class `classes[i]`
+/// becomes class `i`.
+pub fn remap_classes(labels: &Array2<f32>, classes: &[usize]) -> Array2<f32> {
+    labels.select(Axis(1), classes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn one_hot(classes: &[usize], n_classes: usize) -> Array2<f32> {
+        let mut labels = Array2::zeros((classes.len(), n_classes));
+        for (i, &c) in classes.iter().enumerate() {
+            labels[[i, c]] = 1.;
+        }
+        labels
+    }
+
+    #[test]
+    fn counts_and_class_indices() {
+        let classes = vec![2usize, 0, 2, 2, 1, 0];
+        assert_eq!(class_counts(&classes), vec![2, 1, 3]);
+        assert_eq!(class_counts(&one_hot(&classes, 5)), vec![2, 1, 3]);
+        assert_eq!(class_counts(&Vec::<usize>::new()), Vec::<usize>::new());
+        assert_eq!(class_indices(&classes, &[0, 1]), vec![1, 4, 5]);
+        assert_eq!(class_indices(&one_hot(&classes, 3), &[2]), vec![0, 2, 3]);
+        assert_eq!(class_indices(&classes, &[4]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn filter_by_image_and_label() {
+        let images = Array3::from_shape_fn((4, 2, 2), |(n, _, _)| n as f32);
+        let labels = one_hot(&[0, 1, 0, 1], 2);
+        let indices = filter_indices(&images, &labels, |image, label| {
+            image.sum() > 2. && label[1] == 1.
+        });
+        assert_eq!(indices, vec![1, 3]);
+    }
+
+    #[test]
+    fn capped_per_class() {
+        let classes: Vec<usize> = (0..60).map(|i| i % 3).collect();
+        let indices = capped_indices(&classes, &[5, 0, 30], 7);
+        assert!(indices.windows(2).all(|w| w[0] < w[1]));
+        let kept: Vec<usize> = indices.iter().map(|&i| classes[i]).collect();
+        assert_eq!(class_counts(&kept), vec![5, 0, 20]);
+        assert_eq!(indices, capped_indices(&classes, &[5, 0, 30], 7));
+        assert_ne!(indices, capped_indices(&classes, &[5, 0, 30], 8));
+        // Classes without a cap are dropped.
+        let kept: Vec<usize> = capped_indices(&classes, &[2], 0)
+            .iter()
+            .map(|&i| classes[i])
+            .collect();
+        assert_eq!(kept, vec![0, 0]);
+    }
+
+    #[test]
+    fn remap_selected_classes() {
+        let labels = one_hot(&[3, 1, 3], 4);
+        assert_eq!(
+            remap_classes(&labels, &[3, 1]),
+            arr2(&[[1., 0.], [0., 1.], [1., 0.]])
+        );
+    }
+}
@@ -0,0 +1,411 @@
+//! Seeded generators for toy datasets, following scikit-learn's `make_*` functions.
+//!
+//! Every generator returns a feature matrix with one sample per row and the targets, class
+//! indices for classification and real values for regression. Samples are shuffled, the same
+//! seed always yields the same dataset.
+
+use ndarray::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use std::f64::consts::PI;
+
+use crate::sampling::standard_normal;
+
+/// Shuffle the rows of `x` and the entries of `y` with the same permutation.
+fn shuffled<T: Clone>(x: Array2<f64>, y: Vec<T>, rng: &mut StdRng) -> (Array2<f64>, Array1<T>) {
+    let mut order: Vec<usize> = (0..y.len()).collect();
+    order.shuffle(rng);
+    let y = order.iter().map(|&i| y[i].clone()).collect();
+    (x.select(Axis(0), &order), y)
+}
+
+fn add_noise(x: &mut Array2<f64>, noise: f64, rng: &mut StdRng) {
+    if noise > 0. {
+        x.mapv_inplace(|v| v + noise * standard_normal(rng));
+    }
+}
+
+/// Two interleaving half circles. The upper moon is class 0, the lower one class 1.
+pub fn make_moons(n_samples: usize, noise: f64, seed: u64) -> (Array2<f64>, Array1<usize>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let n_out = n_samples / 2;
+    let n_in = n_samples - n_out;
+    let angle = |i: usize, n: usize| {
+        if n > 1 {
+            PI * i as f64 / (n - 1) as f64
+        } else {
+            0.
+        }
+    };
+    let mut x: Array2<f64> = Array2::zeros((n_samples, 2));
+    let mut y = Vec::with_capacity(n_samples);
+    for i in 0..n_out {
+        let t = angle(i, n_out);
+        x.row_mut(i).assign(&array![t.cos(), t.sin()]);
+        y.push(0);
+    }
+    for i in 0..n_in {
+        let t = angle(i, n_in);
+        x.row_mut(n_out + i)
+            .assign(&array![1. - t.cos(), 0.5 - t.sin()]);
+        y.push(1);
+    }
+    add_noise(&mut x, noise, &mut rng);
+    shuffled(x, y, &mut rng)
+}
+
+/// A small circle (class 1) inside a large one (class 0). `factor` is the ratio of their
+/// radii, between 0 and 1.
+pub fn make_circles(
+    n_samples: usize,
+    noise: f64,
+    factor: f64,
+    seed: u64,
+) -> (Array2<f64>, Array1<usize>) {
+    assert!(
+        (0. ..1.).contains(&factor),
+        "Factor has to be between 0 and 1, got {}.",
+        factor
+    );
+    let mut rng = StdRng::seed_from_u64(seed);
+    let n_out = n_samples / 2;
+    let n_in = n_samples - n_out;
+    let mut x: Array2<f64> = Array2::zeros((n_samples, 2));
+    let mut y = Vec::with_capacity(n_samples);
+    for (class, (offset, n, radius)) in [(0, n_out, 1.), (n_out, n_in, factor)].iter().enumerate() {
+        for i in 0..*n {
+            let t = 2. * PI * i as f64 / *n as f64;
+            x.row_mut(offset + i)
+                .assign(&array![radius * t.cos(), radius * t.sin()]);
+            y.push(class);
+        }
+    }
+    add_noise(&mut x, noise, &mut rng);
+    shuffled(x, y, &mut rng)
+}
+
+/// Isotropic Gaussian blobs with standard deviation `cluster_std` around `centers` centers
+/// drawn uniformly from [-10, 10] in every dimension. The label is the index of the blob.
+pub fn make_blobs(
+    n_samples: usize,
+    n_features: usize,
+    centers: usize,
+    cluster_std: f64,
+    seed: u64,
+) -> (Array2<f64>, Array1<usize>) {
+    assert!(centers > 0, "At least one center is required.");
+    let mut rng = StdRng::seed_from_u64(seed);
+    let center_points: Array2<f64> =
+        Array2::from_shape_fn((centers, n_features), |_| rng.gen_range(-10.0..10.0));
+    let mut x: Array2<f64> = Array2::zeros((n_samples, n_features));
+    // Like scikit-learn, the first `n_samples % centers` blobs get one sample more.
+    let y: Vec<usize> = (0..centers)
+        .flat_map(|c| {
+            std::iter::repeat(c).take(n_samples / centers + (c < n_samples % centers) as usize)
+        })
+        .collect();
+    for (mut row, &c) in x.outer_iter_mut().zip(&y) {
+        for (v, &center) in row.iter_mut().zip(center_points.row(c)) {
+            *v = center + cluster_std * standard_normal(&mut rng);
+        }
+    }
+    shuffled(x, y, &mut rng)
+}
+
+/// Random `n_features` dimensional inputs with targets given by a random linear model of the
+/// first `n_informative` features plus Gaussian noise of standard deviation `noise`.
+///
+/// Returns the features, the targets and the coefficients of the linear model.
+pub fn make_regression(
+    n_samples: usize,
+    n_features: usize,
+    n_informative: usize,
+    noise: f64,
+    seed: u64,
+) -> (Array2<f64>, Array1<f64>, Array1<f64>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let n_informative = n_informative.min(n_features);
+    let x: Array2<f64> =
+        Array2::from_shape_fn((n_samples, n_features), |_| standard_normal(&mut rng));
+    let coef: Array1<f64> = Array1::from_shape_fn(n_features, |i| {
+        if i < n_informative {
+            100. * rng.gen::<f64>()
+        } else {
+            0.
+        }
+    });
+    let mut y = x.dot(&coef);
+    y.mapv_inplace(|v| v + noise * standard_normal(&mut rng));
+    let (x, y) = shuffled(x, y.to_vec(), &mut rng);
+    (x, y, coef)
+}
+
+/// Two intertwined spirals of two turns each, with radius growing from 0 to 1. Class 1 is
+/// class 0 rotated by 180 degrees.
+pub fn make_spirals(n_samples: usize, noise: f64, seed: u64) -> (Array2<f64>, Array1<usize>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let turns = 2.;
+    let mut x: Array2<f64> = Array2::zeros((n_samples, 2));
+    let mut y = Vec::with_capacity(n_samples);
+    for (i, mut row) in x.outer_iter_mut().enumerate() {
+        let class = i % 2;
+        // The square root spreads the points evenly along the spiral.
+        let r = rng.gen::<f64>().sqrt();
+        let t = 2. * PI * turns * r;
+        let sign = if class == 0 { 1. } else { -1. };
+        row.assign(&array![sign * r * t.cos(), sign * r * t.sin()]);
+        y.push(class);
+    }
+    add_noise(&mut x, noise, &mut rng);
+    shuffled(x, y, &mut rng)
+}
+
+/// Points drawn uniformly from [-1, 1]^2, labelled 1 if their coordinates have different
+/// signs. The noise is added after labelling.
+pub fn make_xor(n_samples: usize, noise: f64, seed: u64) -> (Array2<f64>, Array1<usize>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut x: Array2<f64> = Array2::from_shape_fn((n_samples, 2), |_| rng.gen_range(-1.0..1.0));
+    let y: Vec<usize> = x
+        .outer_iter()
+        .map(|p| ((p[0] < 0.) != (p[1] < 0.)) as usize)
+        .collect();
+    add_noise(&mut x, noise, &mut rng);
+    shuffled(x, y, &mut rng)
+}
+
+/// Configures a random n-class classification problem like scikit-learn's
+/// `make_classification`.
+///
+/// Every class consists of `clusters_per_class` Gaussian clusters, placed on vertices of a
+/// hypercube with side length `2 * class_sep` in the subspace of the informative features and
+/// randomly covaried. The features are, in this order, the informative ones, redundant random
+/// linear combinations of them and pure noise. Finally the labels of a `flip_y` fraction of the
+/// samples are replaced by random classes.
+#[derive(Clone, Debug)]
+pub struct Classification {
+    n_samples: usize,
+    n_features: usize,
+    n_informative: usize,
+    n_redundant: usize,
+    n_classes: usize,
+    clusters_per_class: usize,
+    class_sep: f64,
+    flip_y: f64,
+    seed: u64,
+}
+
+impl Classification {
+    /// Two classes with two clusters each, two informative and two redundant features.
+    pub fn new(n_samples: usize, n_features: usize) -> Classification {
+        Classification {
+            n_samples,
+            n_features,
+            n_informative: 2,
+            n_redundant: 2,
+            n_classes: 2,
+            clusters_per_class: 2,
+            class_sep: 1.,
+            flip_y: 0.01,
+            seed: 0,
+        }
+    }
+
+    pub fn informative(mut self, n_informative: usize) -> Classification {
+        self.n_informative = n_informative;
+        self
+    }
+
+    pub fn redundant(mut self, n_redundant: usize) -> Classification {
+        self.n_redundant = n_redundant;
+        self
+    }
+
+    pub fn classes(mut self, n_classes: usize) -> Classification {
+        self.n_classes = n_classes;
+        self
+    }
+
+    pub fn clusters_per_class(mut self, clusters_per_class: usize) -> Classification {
+        self.clusters_per_class = clusters_per_class;
+        self
+    }
+
+    /// Half the side length of the hypercube, larger values make the task easier.
+    pub fn class_sep(mut self, class_sep: f64) -> Classification {
+        self.class_sep = class_sep;
+        self
+    }
+
+    /// Fraction of samples whose class is assigned randomly.
+    pub fn flip_y(mut self, flip_y: f64) -> Classification {
+        self.flip_y = flip_y;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Classification {
+        self.seed = seed;
+        self
+    }
+
+    pub fn generate(&self) -> (Array2<f64>, Array1<usize>) {
+        let (n_inf, n_red) = (self.n_informative, self.n_redundant);
+        assert!(
+            n_inf + n_red <= self.n_features,
+            "{} informative and {} redundant features exceed the {} features.",
+            n_inf,
+            n_red,
+            self.n_features
+        );
+        let n_clusters = self.n_classes * self.clusters_per_class;
+        assert!(
+            n_inf < usize::BITS as usize && n_clusters > 0 && n_clusters <= 1 << n_inf,
+            "{} classes times {} clusters don't fit on the hypercube of {} informative features.",
+            self.n_classes,
+            self.clusters_per_class,
+            n_inf
+        );
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        // Distinct hypercube vertices as cluster centroids.
+        let vertices: Vec<usize> = rand::seq::index::sample(&mut rng, 1 << n_inf, n_clusters)
+            .into_iter()
+            .collect();
+        let centroids = Array2::from_shape_fn((n_clusters, n_inf), |(k, j)| {
+            if vertices[k] >> j & 1 == 1 {
+                self.class_sep
+            } else {
+                -self.class_sep
+            }
+        });
+
+        let mut x: Array2<f64> = Array2::zeros((self.n_samples, self.n_features));
+        let mut y: Vec<usize> = Vec::with_capacity(self.n_samples);
+        let mut start = 0;
+        for k in 0..n_clusters {
+            let len = self.n_samples / n_clusters + (k < self.n_samples % n_clusters) as usize;
+            let cluster: Array2<f64> =
+                Array2::from_shape_fn((len, n_inf), |_| standard_normal(&mut rng));
+            let covariance: Array2<f64> =
+                Array2::from_shape_fn((n_inf, n_inf), |_| 2. * rng.gen::<f64>() - 1.);
+            let cluster = cluster.dot(&covariance) + centroids.row(k);
+            x.slice_mut(s![start..start + len, ..n_inf])
+                .assign(&cluster);
+            y.extend(std::iter::repeat(k % self.n_classes).take(len));
+            start += len;
+        }
+
+        let combination: Array2<f64> =
+            Array2::from_shape_fn((n_inf, n_red), |_| 2. * rng.gen::<f64>() - 1.);
+        let redundant = x.slice(s![.., ..n_inf]).dot(&combination);
+        x.slice_mut(s![.., n_inf..n_inf + n_red]).assign(&redundant);
+        x.slice_mut(s![.., n_inf + n_red..])
+            .mapv_inplace(|_| standard_normal(&mut rng));
+
+        for label in y.iter_mut() {
+            if rng.gen::<f64>() < self.flip_y {
+                *label = rng.gen_range(0..self.n_classes);
+            }
+        }
+        shuffled(x, y, &mut rng)
+    }
+}
+
+/// `Classification::new(n_samples, n_features)` with all other settings at their defaults.
+pub fn make_classification(
+    n_samples: usize,
+    n_features: usize,
+    seed: u64,
+) -> (Array2<f64>, Array1<usize>) {
+    Classification::new(n_samples, n_features)
+        .seed(seed)
+        .generate()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class_counts(y: &Array1<usize>) -> Vec<usize> {
+        crate::subset::class_counts(&y.to_vec())
+    }
+
+    #[test]
+    fn same_seed_same_dataset() {
+        type Generator = fn(u64) -> (Array2<f64>, Array1<usize>);
+        let generators: Vec<Generator> = vec![
+            |seed| make_moons(50, 0.1, seed),
+            |seed| make_circles(50, 0.1, 0.5, seed),
+            |seed| make_blobs(50, 3, 4, 1., seed),
+            |seed| make_spirals(50, 0.1, seed),
+            |seed| make_xor(50, 0.1, seed),
+            |seed| make_classification(50, 6, seed),
+        ];
+        for generate in &generators {
+            assert_eq!(generate(3), generate(3));
+            assert_ne!(generate(3).0, generate(4).0);
+        }
+        let regression = make_regression(50, 5, 2, 0.1, 3);
+        assert_eq!(regression, make_regression(50, 5, 2, 0.1, 3));
+        assert_ne!(regression.0, make_regression(50, 5, 2, 0.1, 4).0);
+    }
+
+    #[test]
+    fn moons_without_noise_lie_on_the_half_circles() {
+        let (x, y) = make_moons(101, 0., 0);
+        assert_eq!(x.dim(), (101, 2));
+        assert_eq!(class_counts(&y), vec![50, 51]);
+        for (p, &class) in x.outer_iter().zip(&y) {
+            let (cx, cy, side) = if class == 0 {
+                (0., 0., p[1])
+            } else {
+                (1., 0.5, 0.5 - p[1])
+            };
+            let radius = ((p[0] - cx).powi(2) + (p[1] - cy).powi(2)).sqrt();
+            assert!((radius - 1.).abs() < 1e-12);
+            assert!(side >= -1e-12);
+        }
+    }
+
+    #[test]
+    fn class_counts_of_blobs_and_classification() {
+        let (x, y) = make_blobs(10, 2, 3, 0.5, 0);
+        assert_eq!(x.dim(), (10, 2));
+        assert_eq!(class_counts(&y), vec![4, 3, 3]);
+
+        let (x, y) = Classification::new(100, 6).flip_y(0.).generate();
+        assert_eq!(x.dim(), (100, 6));
+        assert_eq!(class_counts(&y), vec![50, 50]);
+        let (_, y) = Classification::new(10, 5)
+            .informative(3)
+            .classes(3)
+            .clusters_per_class(1)
+            .flip_y(0.)
+            .generate();
+        assert_eq!(class_counts(&y), vec![4, 3, 3]);
+        // Redundant features are linear combinations of the informative ones: solving for
+        // the coefficients on the first two samples reproduces all others.
+        let (x, _) = Classification::new(20, 4).generate();
+        let (a, b) = (x.row(0), x.row(1));
+        let det = a[0] * b[1] - a[1] * b[0];
+        for k in 2..4 {
+            let c0 = (a[k] * b[1] - a[1] * b[k]) / det;
+            let c1 = (a[0] * b[k] - a[k] * b[0]) / det;
+            for row in x.outer_iter() {
+                assert!((c0 * row[0] + c1 * row[1] - row[k]).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn noiseless_targets() {
+        let (x, y) = make_xor(200, 0., 1);
+        for (p, &class) in x.outer_iter().zip(&y) {
+            assert_eq!(class == 1, p[0] * p[1] < 0.);
+        }
+        let (x, y, coef) = make_regression(30, 5, 2, 0., 1);
+        assert_eq!(coef.iter().filter(|&&c| c != 0.).count(), 2);
+        assert!((x.dot(&coef) - &y).iter().all(|d| d.abs() < 1e-9));
+    }
+}
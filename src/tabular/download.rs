@@ -0,0 +1,32 @@
+use std::path::Path;
+
+use crate::download_helper::downloader;
+
+const UCI_BASE_URL: &str = "https://archive.ics.uci.edu/ml/machine-learning-databases";
+const CALIFORNIA_HOUSING_URL: &str = "https://ndownloader.figshare.com/files/5976036";
+const CALIFORNIA_HOUSING_ARCHIVE: &str = "cal_housing.tgz";
+
+/// Download `files` from the UCI folder `dataset`, e.g. `iris`.
+pub fn download_uci(base_path: &str, dataset: &str, files: &[&str]) -> Result<(), String> {
+    println!("Attempting to download {}...", dataset);
+    let base_url = format!("{}/{}", UCI_BASE_URL, dataset);
+    downloader::download(base_path, base_url, files.to_vec())
+}
+
+pub fn download_and_extract_california_housing(base_path: &str) -> Result<(), String> {
+    println!("Attempting to download and extract California Housing...");
+    downloader::download_file(
+        base_path,
+        CALIFORNIA_HOUSING_URL.to_string(),
+        CALIFORNIA_HOUSING_ARCHIVE,
+    )?;
+    let base_dir = Path::new(base_path);
+    if base_dir.join("CaliforniaHousing").exists() {
+        println!(
+            "  Dataset already extracted to {:?}, skipping extraction.",
+            base_dir
+        );
+        return Ok(());
+    }
+    downloader::extract_tar_gz(&base_dir.join(CALIFORNIA_HOUSING_ARCHIVE), base_dir)
+}
@@ -0,0 +1,322 @@
+//! The small tabular datasets commonly used for quick baselines, in the versions shipped by
+//! scikit-learn: Iris, Wine, Breast Cancer, Digits and California Housing.
+//!
+//! Every dataset module offers `new()` to read the original UCI (or StatLib) files from
+//! `data/<name>/` and, with the `download` feature, `download()` to fetch them.
+
+use ndarray::{Array1, Array2};
+
+use std::fs;
+use std::path::Path;
+
+#[cfg(feature = "download")]
+mod download;
+
+/// Features and targets of a tabular dataset.
+///
+/// `T` is `usize` for classification datasets, where `target[i]` indexes `class_names`, and
+/// `f64` for regression datasets, which have no class names.
+#[derive(Clone, Debug)]
+pub struct Data<T> {
+    pub features: Array2<f64>,
+    pub target: Array1<T>,
+    pub feature_names: Vec<String>,
+    pub class_names: Vec<String>,
+}
+
+impl<T> Data<T> {
+    pub fn len(&self) -> usize {
+        self.target.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.target.is_empty()
+    }
+}
+
+/// Comma separated fields of all non-empty lines of `path`.
+fn read_csv(path: &Path) -> Vec<Vec<String>> {
+    fs::read_to_string(path)
+        .unwrap_or_else(|_| panic!("Unable to read {:?}.", path))
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.split(',').map(|f| f.trim().to_string()).collect())
+        .collect()
+}
+
+fn parse_f64(field: &str, path: &Path) -> f64 {
+    field
+        .parse()
+        .unwrap_or_else(|_| panic!("Unable to parse {:?} in {:?} as number.", field, path))
+}
+
+/// Build a dataset from rows whose feature columns are `features` and whose target is
+/// computed from the whole row.
+fn from_rows<T, F>(
+    path: &Path,
+    rows: &[Vec<String>],
+    features: &[usize],
+    target: F,
+    feature_names: &[&str],
+    class_names: &[&str],
+) -> Data<T>
+where
+    F: Fn(&[String]) -> T,
+{
+    let mut values: Vec<f64> = Vec::with_capacity(rows.len() * features.len());
+    let mut targets: Vec<T> = Vec::with_capacity(rows.len());
+    let needed = features.iter().max().map_or(0, |&c| c + 1);
+    for row in rows {
+        assert!(
+            row.len() >= needed,
+            "Expected at least {} columns in {:?}, got {:?}.",
+            needed,
+            path,
+            row
+        );
+        values.extend(features.iter().map(|&c| parse_f64(&row[c], path)));
+        targets.push(target(row));
+    }
+    Data {
+        features: Array2::from_shape_vec((rows.len(), features.len()), values).unwrap(),
+        target: Array1::from(targets),
+        feature_names: feature_names.iter().map(|s| s.to_string()).collect(),
+        class_names: class_names.iter().map(|s| s.to_string()).collect(),
+    }
+}
+
+fn class_index(field: &str, classes: &[&str], path: &Path) -> usize {
+    classes
+        .iter()
+        .position(|&c| c == field)
+        .unwrap_or_else(|| panic!("Unknown class {:?} in {:?}.", field, path))
+}
+
+fn check_length<T>(data: &Data<T>, expected_length: usize) {
+    assert!(
+        data.len() == expected_length,
+        "Expected data set length of {} got {}.",
+        expected_length,
+        data.len()
+    );
+}
+
+pub mod iris {
+    use super::Data;
+    use std::path::Path;
+
+    static BASE_PATH: &str = "data/iris/";
+    static FILENAME: &str = "iris.data";
+    static LEN: usize = 150;
+    static FEATURE_NAMES: &[&str] = &[
+        "sepal length (cm)",
+        "sepal width (cm)",
+        "petal length (cm)",
+        "petal width (cm)",
+    ];
+    static CLASS_NAMES: &[&str] = &["setosa", "versicolor", "virginica"];
+    static FILE_CLASSES: &[&str] = &["Iris-setosa", "Iris-versicolor", "Iris-virginica"];
+
+    pub fn new() -> Data<usize> {
+        let path = Path::new(BASE_PATH).join(FILENAME);
+        let rows = super::read_csv(&path);
+        let data = super::from_rows(
+            &path,
+            &rows,
+            &[0, 1, 2, 3],
+            |row| super::class_index(&row[4], FILE_CLASSES, &path),
+            FEATURE_NAMES,
+            CLASS_NAMES,
+        );
+        super::check_length(&data, LEN);
+        data
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download() {
+        super::download::download_uci(BASE_PATH, "iris", &[FILENAME]).unwrap();
+    }
+}
+
+pub mod wine {
+    use super::Data;
+    use std::path::Path;
+
+    static BASE_PATH: &str = "data/wine/";
+    static FILENAME: &str = "wine.data";
+    static LEN: usize = 178;
+    static FEATURE_NAMES: &[&str] = &[
+        "alcohol",
+        "malic_acid",
+        "ash",
+        "alcalinity_of_ash",
+        "magnesium",
+        "total_phenols",
+        "flavanoids",
+        "nonflavanoid_phenols",
+        "proanthocyanins",
+        "color_intensity",
+        "hue",
+        "od280/od315_of_diluted_wines",
+        "proline",
+    ];
+    static CLASS_NAMES: &[&str] = &["class_0", "class_1", "class_2"];
+
+    /// Wine cultivars, the first column of `wine.data` holds the class as 1, 2 or 3.
+    pub fn new() -> Data<usize> {
+        let path = Path::new(BASE_PATH).join(FILENAME);
+        let rows = super::read_csv(&path);
+        let features: Vec<usize> = (1..=FEATURE_NAMES.len()).collect();
+        let data = super::from_rows(
+            &path,
+            &rows,
+            &features,
+            |row| super::class_index(&row[0], &["1", "2", "3"], &path),
+            FEATURE_NAMES,
+            CLASS_NAMES,
+        );
+        super::check_length(&data, LEN);
+        data
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download() {
+        super::download::download_uci(BASE_PATH, "wine", &[FILENAME]).unwrap();
+    }
+}
+
+pub mod breast_cancer {
+    use super::Data;
+    use std::path::Path;
+
+    static BASE_PATH: &str = "data/breast_cancer/";
+    static FILENAME: &str = "wdbc.data";
+    static LEN: usize = 569;
+    static MEASUREMENTS: &[&str] = &[
+        "radius",
+        "texture",
+        "perimeter",
+        "area",
+        "smoothness",
+        "compactness",
+        "concavity",
+        "concave points",
+        "symmetry",
+        "fractal dimension",
+    ];
+    static CLASS_NAMES: &[&str] = &["malignant", "benign"];
+
+    /// Wisconsin Diagnostic Breast Cancer. Rows of `wdbc.data` are the sample id, the diagnosis
+    /// `M` or `B` and the mean, standard error and worst value of ten measurements.
+    pub fn new() -> Data<usize> {
+        let path = Path::new(BASE_PATH).join(FILENAME);
+        let rows = super::read_csv(&path);
+        let mut feature_names: Vec<String> = Vec::with_capacity(3 * MEASUREMENTS.len());
+        feature_names.extend(MEASUREMENTS.iter().map(|m| format!("mean {}", m)));
+        feature_names.extend(MEASUREMENTS.iter().map(|m| format!("{} error", m)));
+        feature_names.extend(MEASUREMENTS.iter().map(|m| format!("worst {}", m)));
+        let feature_names: Vec<&str> = feature_names.iter().map(|s| s.as_str()).collect();
+        let features: Vec<usize> = (2..2 + feature_names.len()).collect();
+        let data = super::from_rows(
+            &path,
+            &rows,
+            &features,
+            |row| super::class_index(&row[1], &["M", "B"], &path),
+            &feature_names,
+            CLASS_NAMES,
+        );
+        super::check_length(&data, LEN);
+        data
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download() {
+        super::download::download_uci(BASE_PATH, "breast-cancer-wisconsin", &[FILENAME]).unwrap();
+    }
+}
+
+pub mod digits {
+    use super::Data;
+    use std::path::Path;
+
+    static BASE_PATH: &str = "data/digits/";
+    static FILENAME: &str = "optdigits.tes";
+    static LEN: usize = 1797;
+    static CLASS_NAMES: &[&str] = &["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+    /// 8 x 8 handwritten digits with pixel counts between 0 and 16, flattened row by row.
+    pub fn new() -> Data<usize> {
+        let path = Path::new(BASE_PATH).join(FILENAME);
+        let rows = super::read_csv(&path);
+        let feature_names: Vec<String> = (0..64)
+            .map(|i| format!("pixel_{}_{}", i / 8, i % 8))
+            .collect();
+        let feature_names: Vec<&str> = feature_names.iter().map(|s| s.as_str()).collect();
+        let features: Vec<usize> = (0..64).collect();
+        let data = super::from_rows(
+            &path,
+            &rows,
+            &features,
+            |row| super::class_index(&row[64], CLASS_NAMES, &path),
+            &feature_names,
+            CLASS_NAMES,
+        );
+        super::check_length(&data, LEN);
+        data
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download() {
+        super::download::download_uci(BASE_PATH, "optdigits", &[FILENAME]).unwrap();
+    }
+}
+
+pub mod california_housing {
+    use super::Data;
+    use std::path::Path;
+
+    static BASE_PATH: &str = "data/california_housing/";
+    static FILENAME: &str = "CaliforniaHousing/cal_housing.data";
+    static LEN: usize = 20640;
+    static FEATURE_NAMES: &[&str] = &[
+        "MedInc",
+        "HouseAge",
+        "AveRooms",
+        "AveBedrms",
+        "Population",
+        "AveOccup",
+        "Latitude",
+        "Longitude",
+    ];
+
+    /// Median house values of California block groups in units of 100,000$.
+    ///
+    /// The raw columns are longitude, latitude, median age, total rooms, total bedrooms,
+    /// population, households, median income and median house value. As in scikit-learn the
+    /// room, bedroom and population counts are turned into averages per household.
+    pub fn new() -> Data<f64> {
+        let path = Path::new(BASE_PATH).join(FILENAME);
+        let rows = super::read_csv(&path);
+        let mut data = super::from_rows(
+            &path,
+            &rows,
+            &[7, 2, 3, 4, 5, 6, 1, 0],
+            |row| super::parse_f64(&row[8], &path) / 100_000.,
+            FEATURE_NAMES,
+            &[],
+        );
+        for mut row in data.features.outer_iter_mut() {
+            let households = row[5];
+            row[2] /= households;
+            row[3] /= households;
+            row[5] = row[4] / households;
+        }
+        super::check_length(&data, LEN);
+        data
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download() {
+        super::download::download_and_extract_california_housing(BASE_PATH).unwrap();
+    }
+}
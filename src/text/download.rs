@@ -0,0 +1,29 @@
+use std::path::Path;
+
+use crate::download_helper::downloader;
+
+const IMDB_BASE_URL: &str = "https://ai.stanford.edu/~amaas/data/sentiment";
+const IMDB_ARCHIVE: &str = "aclImdb_v1.tar.gz";
+const AG_NEWS_BASE_URL: &str =
+    "https://raw.githubusercontent.com/mhjabreel/CharCnn_Keras/master/data/ag_news_csv";
+
+pub fn download_and_extract_imdb(base_path: &str) -> Result<(), String> {
+    println!("Attempting to download and extract {}...", IMDB_ARCHIVE);
+    downloader::download(base_path, IMDB_BASE_URL.to_string(), vec![IMDB_ARCHIVE])?;
+    let base_dir = Path::new(base_path);
+    if base_dir.join("train").exists() {
+        println!(
+            "  Dataset already extracted to {:?}, skipping extraction.",
+            base_dir
+        );
+        return Ok(());
+    }
+    downloader::extract_tar_gz(&base_dir.join(IMDB_ARCHIVE), Path::new("data"))?;
+    println!("done unpacking .tar.gz");
+    Ok(())
+}
+
+pub fn download_ag_news(base_path: &str, files: &[&str]) -> Result<(), String> {
+    println!("Attempting to download AG News...");
+    downloader::download(base_path, AG_NEWS_BASE_URL.to_string(), files.to_vec())
+}
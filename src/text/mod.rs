@@ -0,0 +1,346 @@
+//! Text classification datasets and the tools to turn their documents into token ids.
+//!
+//! ```no_run
+//! use datasets::text::{imdb, VocabBuilder};
+//!
+//! let data = imdb::new();
+//! let encoded = data.encode(&VocabBuilder::new().min_freq(5).max_size(20_000), 256);
+//! assert_eq!(encoded.trn_ids.shape(), [25_000, 256]);
+//! ```
+
+use ndarray::Array2;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::image_folder;
+
+#[cfg(feature = "download")]
+mod download;
+
+/// Token used to fill documents shorter than the requested length.
+pub const PAD: &str = "<pad>";
+/// Token replacing words which are not part of the vocabulary.
+pub const UNK: &str = "<unk>";
+
+/// Split `text` into lowercase words, treating HTML line breaks and every character which is
+/// neither alphanumeric nor an apostrophe as separator.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.replace("<br />", " ")
+        .to_lowercase()
+        .split(|c: char| !(c.is_alphanumeric() || c == '\''))
+        .map(|word| word.trim_matches('\''))
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_string())
+        .collect()
+}
+
+/// Mapping between tokens and their ids.
+#[derive(Clone, Debug)]
+pub struct Vocab {
+    itos: Vec<String>,
+    stoi: HashMap<String, u32>,
+}
+
+impl Vocab {
+    /// Number of tokens, including special tokens.
+    pub fn len(&self) -> usize {
+        self.itos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.itos.is_empty()
+    }
+
+    pub fn id(&self, token: &str) -> Option<u32> {
+        self.stoi.get(token).cloned()
+    }
+
+    pub fn token(&self, id: u32) -> Option<&str> {
+        self.itos.get(id as usize).map(|s| s.as_str())
+    }
+
+    /// All tokens, ordered by id.
+    pub fn tokens(&self) -> &[String] {
+        &self.itos
+    }
+
+    /// Id of `PAD`, or 0 if the vocabulary has no padding token.
+    pub fn pad_id(&self) -> u32 {
+        self.id(PAD).unwrap_or(0)
+    }
+
+    /// Ids of `tokens`. Unknown tokens are mapped to `UNK`, or dropped if the vocabulary has
+    /// no such token.
+    pub fn encode<S: AsRef<str>>(&self, tokens: &[S]) -> Vec<u32> {
+        let unk = self.id(UNK);
+        tokens
+            .iter()
+            .filter_map(|token| self.id(token.as_ref()).or(unk))
+            .collect()
+    }
+
+    /// Encode every document into a row of `max_len` ids. Longer documents are truncated,
+    /// shorter ones filled up with `pad_id()` at the end.
+    pub fn encode_padded<S: AsRef<str>>(&self, docs: &[Vec<S>], max_len: usize) -> Array2<u32> {
+        let mut ids: Array2<u32> = Array2::from_elem((docs.len(), max_len), self.pad_id());
+        for (mut row, doc) in ids.outer_iter_mut().zip(docs) {
+            for (x, id) in row.iter_mut().zip(self.encode(doc)) {
+                *x = id;
+            }
+        }
+        ids
+    }
+}
+
+/// Builds a `Vocab` from the token frequencies of a corpus.
+///
+/// Special tokens come first (by default `PAD` with id 0 and `UNK` with id 1), followed by
+/// the corpus tokens ordered by decreasing frequency and alphabetically among equal ones.
+#[derive(Clone, Debug)]
+pub struct VocabBuilder {
+    min_freq: usize,
+    max_size: Option<usize>,
+    specials: Vec<String>,
+}
+
+impl Default for VocabBuilder {
+    fn default() -> Self {
+        VocabBuilder::new()
+    }
+}
+
+impl VocabBuilder {
+    pub fn new() -> VocabBuilder {
+        VocabBuilder {
+            min_freq: 1,
+            max_size: None,
+            specials: vec![PAD.to_string(), UNK.to_string()],
+        }
+    }
+
+    /// Drop tokens occurring fewer than `min_freq` times.
+    pub fn min_freq(mut self, min_freq: usize) -> VocabBuilder {
+        self.min_freq = min_freq;
+        self
+    }
+
+    /// Keep at most `max_size` tokens, including the special tokens.
+    pub fn max_size(mut self, max_size: usize) -> VocabBuilder {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Replace the special tokens, e.g. to add `<bos>` and `<eos>` or to drop `UNK`.
+    pub fn specials(mut self, specials: &[&str]) -> VocabBuilder {
+        self.specials = specials.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    pub fn build<S: AsRef<str>>(&self, docs: &[Vec<S>]) -> Vocab {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for token in docs.iter().flatten() {
+            *counts.entry(token.as_ref()).or_insert(0) += 1;
+        }
+        let mut tokens: Vec<(&str, usize)> = counts
+            .into_iter()
+            .filter(|&(token, count)| {
+                count >= self.min_freq && !self.specials.iter().any(|s| s == token)
+            })
+            .collect();
+        tokens.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        let mut itos = self.specials.clone();
+        itos.extend(tokens.into_iter().map(|(token, _)| token.to_string()));
+        if let Some(max_size) = self.max_size {
+            itos.truncate(max_size.max(self.specials.len()));
+        }
+        let stoi = itos
+            .iter()
+            .enumerate()
+            .map(|(i, token)| (token.clone(), i as u32))
+            .collect();
+        Vocab { itos, stoi }
+    }
+}
+
+/// Raw documents of a text classification dataset.
+///
+/// `trn_lbl[i]` is the class index of `trn_text[i]` into `class_names`.
+#[derive(Clone, Debug)]
+pub struct Data {
+    pub trn_text: Vec<String>,
+    pub trn_lbl: Vec<usize>,
+    pub tst_text: Vec<String>,
+    pub tst_lbl: Vec<usize>,
+    pub class_names: Vec<String>,
+}
+
+/// Token ids of a text classification dataset, with one-hot labels like the image datasets.
+pub struct Encoded {
+    pub vocab: Vocab,
+    pub trn_ids: Array2<u32>,
+    pub trn_lbl: Array2<f32>,
+    pub tst_ids: Array2<u32>,
+    pub tst_lbl: Array2<f32>,
+}
+
+impl Data {
+    /// Tokenize all documents, build the vocabulary from the training documents and encode
+    /// both splits into `max_len` ids per document.
+    pub fn encode(&self, builder: &VocabBuilder, max_len: usize) -> Encoded {
+        let trn_tokens: Vec<Vec<String>> = self.trn_text.iter().map(|t| tokenize(t)).collect();
+        let tst_tokens: Vec<Vec<String>> = self.tst_text.iter().map(|t| tokenize(t)).collect();
+        let vocab = builder.build(&trn_tokens);
+        let classes = self.class_names.len();
+        Encoded {
+            trn_ids: vocab.encode_padded(&trn_tokens, max_len),
+            trn_lbl: image_folder::one_hot(&self.trn_lbl, classes),
+            tst_ids: vocab.encode_padded(&tst_tokens, max_len),
+            tst_lbl: image_folder::one_hot(&self.tst_lbl, classes),
+            vocab,
+        }
+    }
+}
+
+/// Reviews of `dir/<class>/*.txt` for every class, ordered by class and file name.
+fn read_review_folders(dir: &Path, classes: &[&str]) -> (Vec<String>, Vec<usize>) {
+    let mut texts = Vec::new();
+    let mut labels = Vec::new();
+    for (label, class) in classes.iter().enumerate() {
+        let class_dir = dir.join(class);
+        let mut paths: Vec<_> = fs::read_dir(&class_dir)
+            .unwrap_or_else(|_| panic!("Unable to read directory {:?}.", class_dir))
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "txt"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            texts.push(
+                fs::read_to_string(&path).unwrap_or_else(|_| panic!("Unable to read {:?}.", path)),
+            );
+            labels.push(label);
+        }
+    }
+    (texts, labels)
+}
+
+/// Records of a CSV file, with quoted fields which may contain commas, newlines and `""`.
+fn read_csv_records(path: &Path) -> Vec<Vec<String>> {
+    let content = fs::read_to_string(path).unwrap_or_else(|_| panic!("Unable to read {:?}.", path));
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            '\r' if !quoted => {}
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|r| !(r.len() == 1 && r[0].is_empty()));
+    records
+}
+
+fn check_length(texts: &[String], expected_length: usize) {
+    assert!(
+        texts.len() == expected_length,
+        "Expected data set length of {} got {}.",
+        expected_length,
+        texts.len()
+    );
+}
+
+/// Large Movie Review Dataset: 25,000 training and 25,000 test reviews from IMDB, labelled
+/// as negative (0) or positive (1). The unlabelled reviews are not loaded.
+pub mod imdb {
+    pub use super::Data;
+    use std::path::Path;
+
+    static BASE_PATH: &str = "data/aclImdb/";
+    static CLASSES: &[&str] = &["neg", "pos"];
+    static LEN: usize = 25_000;
+
+    pub fn new() -> Data {
+        let base_path = Path::new(BASE_PATH);
+        let (trn_text, trn_lbl) = super::read_review_folders(&base_path.join("train"), CLASSES);
+        let (tst_text, tst_lbl) = super::read_review_folders(&base_path.join("test"), CLASSES);
+        super::check_length(&trn_text, LEN);
+        super::check_length(&tst_text, LEN);
+        Data {
+            trn_text,
+            trn_lbl,
+            tst_text,
+            tst_lbl,
+            class_names: CLASSES.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download_and_extract() {
+        super::download::download_and_extract_imdb(BASE_PATH).unwrap();
+    }
+}
+
+/// AG News: 120,000 training and 7,600 test news articles of four topics. The text of an
+/// article is its title and description, separated by a space.
+pub mod ag_news {
+    pub use super::Data;
+    use std::path::Path;
+
+    static BASE_PATH: &str = "data/ag_news/";
+    static FILES: &[&str] = &["train.csv", "test.csv"];
+    static CLASSES: &[&str] = &["World", "Sports", "Business", "Sci/Tech"];
+    static TRN_LEN: usize = 120_000;
+    static TST_LEN: usize = 7_600;
+
+    /// Rows of the CSV files are `"<class 1-4>","<title>","<description>"`.
+    fn read(file: &str) -> (Vec<String>, Vec<usize>) {
+        let path = Path::new(BASE_PATH).join(file);
+        super::read_csv_records(&path)
+            .into_iter()
+            .map(|record| {
+                let label = match record.first().and_then(|l| l.parse::<usize>().ok()) {
+                    Some(label) if (1..=CLASSES.len()).contains(&label) => label - 1,
+                    _ => panic!("Invalid record in {:?}: {:?}", path, record),
+                };
+                (record[1..].join(" "), label)
+            })
+            .unzip()
+    }
+
+    pub fn new() -> Data {
+        let (trn_text, trn_lbl) = read(FILES[0]);
+        let (tst_text, tst_lbl) = read(FILES[1]);
+        super::check_length(&trn_text, TRN_LEN);
+        super::check_length(&tst_text, TST_LEN);
+        Data {
+            trn_text,
+            trn_lbl,
+            tst_text,
+            tst_lbl,
+            class_names: CLASSES.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download() {
+        super::download::download_ag_news(BASE_PATH, FILES).unwrap();
+    }
+}
@@ -0,0 +1,902 @@
+//! Seeded image augmentations.
+//!
+//! A `Transform` works on a single image in CHW layout. The batch helpers `apply_nchw` and
+//! `apply_nhw` augment the `Array4` images of `cifar_builder::Data` and the `Array3` images of
+//! `mnist_builder::Data` in parallel. Every image gets its own random generator derived from
+//! the seed and its index, so results don't depend on the number of threads.
+//!
+//! ```no_run
+//! use datasets::cifar10;
+//! use datasets::transforms::{Compose, RandomCrop, RandomHorizontalFlip, Transform};
+//!
+//! let data = cifar10::new();
+//! let augment = Compose::new(vec![
+//!     Box::new(RandomCrop::new(32, 32, 4)),
+//!     Box::new(RandomHorizontalFlip::new(0.5)),
+//! ]);
+//! let epoch_0 = augment.apply_nchw(&data.trn_img, 0);
+//! ```
+
+use ndarray::prelude::*;
+use ndarray::Zip;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::sampling::{beta, standard_normal};
+
+/// One step of the splitmix64 generator, a bijection that scatters nearby inputs.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Random generator of image `index` of a batch augmented with `seed`. The seed is mixed
+/// before adding the index, so nearby seeds don't share the streams of shifted indices.
+pub(crate) fn image_rng(seed: u64, index: usize) -> StdRng {
+    StdRng::seed_from_u64(splitmix64(splitmix64(seed).wrapping_add(index as u64)))
+}
+
+fn uniform(rng: &mut StdRng, low: f32, high: f32) -> f32 {
+    low + (high - low) * rng.gen::<f32>()
+}
+
+pub trait Transform: Sync {
+    /// Transform a single `(channels, rows, cols)` image.
+    fn apply(&self, image: Array3<f32>, rng: &mut StdRng) -> Array3<f32>;
+
+    /// Transform every image of an NCHW batch, e.g. `cifar10::Data::trn_img`.
+    fn apply_nchw(&self, images: &Array4<f32>, seed: u64) -> Array4<f32> {
+        let outputs: Vec<Array3<f32>> = (0..images.len_of(Axis(0)))
+            .into_par_iter()
+            .map(|i| {
+                let image = images.index_axis(Axis(0), i).to_owned();
+                self.apply(image, &mut image_rng(seed, i))
+            })
+            .collect();
+        stack_images(images.dim(), outputs)
+    }
+
+    /// Transform every image of an NHW batch, e.g. `mnist::Data::trn_img`.
+    fn apply_nhw(&self, images: &Array3<f32>, seed: u64) -> Array3<f32> {
+        let (n, rows, cols) = images.dim();
+        let images = images.view().into_shape((n, 1, rows, cols)).unwrap();
+        let images = self.apply_nchw(&images.to_owned(), seed);
+        images.index_axis_move(Axis(1), 0)
+    }
+}
+
+fn stack_images(dim: (usize, usize, usize, usize), outputs: Vec<Array3<f32>>) -> Array4<f32> {
+    let mut batch: Array4<f32> = Array4::zeros(dim);
+    for (mut target, image) in batch.outer_iter_mut().zip(outputs) {
+        assert!(
+            target.shape() == image.shape(),
+            "Transforms changed the image shape from {:?} to {:?}.",
+            target.shape(),
+            image.shape()
+        );
+        target.assign(&image);
+    }
+    batch
+}
+
+/// Applies several transforms one after another.
+pub struct Compose {
+    transforms: Vec<Box<dyn Transform>>,
+}
+
+impl Compose {
+    pub fn new(transforms: Vec<Box<dyn Transform>>) -> Compose {
+        Compose { transforms }
+    }
+}
+
+impl Transform for Compose {
+    fn apply(&self, image: Array3<f32>, rng: &mut StdRng) -> Array3<f32> {
+        self.transforms
+            .iter()
+            .fold(image, |image, transform| transform.apply(image, rng))
+    }
+}
+
+/// Pads the image with `padding` zeros on every side and crops a random `rows` x `cols` window,
+/// the standard CIFAR augmentation.
+pub struct RandomCrop {
+    rows: usize,
+    cols: usize,
+    padding: usize,
+}
+
+impl RandomCrop {
+    pub fn new(rows: usize, cols: usize, padding: usize) -> RandomCrop {
+        RandomCrop {
+            rows,
+            cols,
+            padding,
+        }
+    }
+}
+
+impl Transform for RandomCrop {
+    fn apply(&self, image: Array3<f32>, rng: &mut StdRng) -> Array3<f32> {
+        let (channels, rows, cols) = image.dim();
+        let p = self.padding;
+        let (padded_rows, padded_cols) = (rows + 2 * p, cols + 2 * p);
+        assert!(
+            self.rows <= padded_rows && self.cols <= padded_cols,
+            "Cannot crop {} x {} from a padded image of {} x {}.",
+            self.rows,
+            self.cols,
+            padded_rows,
+            padded_cols
+        );
+        let top = rng.gen_range(0..=padded_rows - self.rows);
+        let left = rng.gen_range(0..=padded_cols - self.cols);
+        let mut out: Array3<f32> = Array3::zeros((channels, self.rows, self.cols));
+        // Intersection of the crop window with the unpadded image, in padded coordinates.
+        let (r0, r1) = (top.max(p), (top + self.rows).min(p + rows));
+        let (c0, c1) = (left.max(p), (left + self.cols).min(p + cols));
+        if r0 < r1 && c0 < c1 {
+            out.slice_mut(s![.., r0 - top..r1 - top, c0 - left..c1 - left])
+                .assign(&image.slice(s![.., r0 - p..r1 - p, c0 - p..c1 - p]));
+        }
+        out
+    }
+}
+
+/// Mirrors the image left to right with probability `p`.
+pub struct RandomHorizontalFlip {
+    p: f32,
+}
+
+impl RandomHorizontalFlip {
+    pub fn new(p: f32) -> RandomHorizontalFlip {
+        RandomHorizontalFlip { p }
+    }
+}
+
+impl Transform for RandomHorizontalFlip {
+    fn apply(&self, mut image: Array3<f32>, rng: &mut StdRng) -> Array3<f32> {
+        if rng.gen::<f32>() < self.p {
+            image.invert_axis(Axis(2));
+            image = image.as_standard_layout().to_owned();
+        }
+        image
+    }
+}
+
+/// Bilinear interpolation of `channel` at the fractional position `(row, col)`. Pixels outside
+/// of the image take the value `fill`.
+pub(crate) fn bilinear(channel: &ArrayView2<f32>, row: f32, col: f32, fill: f32) -> f32 {
+    let (rows, cols) = channel.dim();
+    let (r0, c0) = (row.floor(), col.floor());
+    let (dr, dc) = (row - r0, col - c0);
+    let pixel = |r: f32, c: f32| {
+        if r < 0. || c < 0. || r >= rows as f32 || c >= cols as f32 {
+            fill
+        } else {
+            channel[[r as usize, c as usize]]
+        }
+    };
+    (1. - dr) * ((1. - dc) * pixel(r0, c0) + dc * pixel(r0, c0 + 1.))
+        + dr * ((1. - dc) * pixel(r0 + 1., c0) + dc * pixel(r0 + 1., c0 + 1.))
+}
+
+/// Warp every channel with the 2x2 `matrix` around the image center followed by a shift of
+/// `translate` pixels, `(right, down)`. The matrix acts on `(x, y)` coordinates with the y axis
+/// pointing up, so a rotation matrix rotates counter-clockwise.
+fn warp_affine(image: &Array3<f32>, matrix: [[f32; 2]; 2], translate: (f32, f32)) -> Array3<f32> {
+    let (_, rows, cols) = image.dim();
+    let (cy, cx) = ((rows as f32 - 1.) / 2., (cols as f32 - 1.) / 2.);
+    let [[a, b], [c, d]] = matrix;
+    let det = a * d - b * c;
+    let inverse = [[d / det, -b / det], [-c / det, a / det]];
+    let mut out: Array3<f32> = Array3::zeros(image.dim());
+    for (mut out_channel, channel) in out.outer_iter_mut().zip(image.outer_iter()) {
+        for ((r, col), value) in out_channel.indexed_iter_mut() {
+            let x = col as f32 - cx - translate.0;
+            let y = cy - r as f32 + translate.1;
+            let xs = inverse[0][0] * x + inverse[0][1] * y;
+            let ys = inverse[1][0] * x + inverse[1][1] * y;
+            *value = bilinear(&channel, cy - ys, xs + cx, 0.);
+        }
+    }
+    out
+}
+
+/// Rotates, shifts, scales and shears the image by random amounts, filling uncovered pixels
+/// with zeros.
+///
+/// The angle is drawn from `[-degrees, degrees]`, the shift from `[-translate.0 * cols,
+/// translate.0 * cols]` horizontally and `[-translate.1 * rows, translate.1 * rows]`
+/// vertically, the scale from `[scale.0, scale.1]` and the horizontal shear angle from
+/// `[-shear, shear]` degrees.
+pub struct RandomAffine {
+    degrees: f32,
+    translate: (f32, f32),
+    scale: (f32, f32),
+    shear: f32,
+}
+
+impl RandomAffine {
+    /// The identity, configure the ranges with the other methods.
+    pub fn new() -> RandomAffine {
+        RandomAffine {
+            degrees: 0.,
+            translate: (0., 0.),
+            scale: (1., 1.),
+            shear: 0.,
+        }
+    }
+
+    pub fn degrees(mut self, degrees: f32) -> RandomAffine {
+        self.degrees = degrees;
+        self
+    }
+
+    /// Maximal shifts as fraction of the image width and height.
+    pub fn translate(mut self, horizontal: f32, vertical: f32) -> RandomAffine {
+        self.translate = (horizontal, vertical);
+        self
+    }
+
+    pub fn scale(mut self, min: f32, max: f32) -> RandomAffine {
+        self.scale = (min, max);
+        self
+    }
+
+    pub fn shear(mut self, degrees: f32) -> RandomAffine {
+        self.shear = degrees;
+        self
+    }
+}
+
+impl Default for RandomAffine {
+    fn default() -> Self {
+        RandomAffine::new()
+    }
+}
+
+impl Transform for RandomAffine {
+    fn apply(&self, image: Array3<f32>, rng: &mut StdRng) -> Array3<f32> {
+        let (_, rows, cols) = image.dim();
+        let angle = uniform(rng, -self.degrees, self.degrees).to_radians();
+        let dx = uniform(rng, -self.translate.0, self.translate.0) * cols as f32;
+        let dy = uniform(rng, -self.translate.1, self.translate.1) * rows as f32;
+        let scale = uniform(rng, self.scale.0, self.scale.1);
+        let shear = uniform(rng, -self.shear, self.shear).to_radians().tan();
+        let (sin, cos) = angle.sin_cos();
+        // scale * rotation * [[1, shear], [0, 1]]
+        let matrix = [
+            [scale * cos, scale * (cos * shear - sin)],
+            [scale * sin, scale * (sin * shear + cos)],
+        ];
+        warp_affine(&image, matrix, (dx.round(), dy.round()))
+    }
+}
+
+/// Rotates the image counter-clockwise by an angle drawn from `[-degrees, degrees]`.
+pub struct RandomRotation {
+    affine: RandomAffine,
+}
+
+impl RandomRotation {
+    pub fn new(degrees: f32) -> RandomRotation {
+        RandomRotation {
+            affine: RandomAffine::new().degrees(degrees),
+        }
+    }
+}
+
+impl Transform for RandomRotation {
+    fn apply(&self, image: Array3<f32>, rng: &mut StdRng) -> Array3<f32> {
+        self.affine.apply(image, rng)
+    }
+}
+
+/// Shifts the image by up to a fraction `horizontal` of its width and `vertical` of its
+/// height, by whole pixels.
+pub struct RandomTranslation {
+    affine: RandomAffine,
+}
+
+impl RandomTranslation {
+    pub fn new(horizontal: f32, vertical: f32) -> RandomTranslation {
+        RandomTranslation {
+            affine: RandomAffine::new().translate(horizontal, vertical),
+        }
+    }
+}
+
+impl Transform for RandomTranslation {
+    fn apply(&self, image: Array3<f32>, rng: &mut StdRng) -> Array3<f32> {
+        self.affine.apply(image, rng)
+    }
+}
+
+/// Randomly changes brightness, contrast, saturation and hue, in this order.
+///
+/// Brightness, contrast and saturation are scaled by factors drawn from `[1 - x, 1 + x]`, the
+/// hue is rotated by up to `hue` turns (at most 0.5). Saturation and hue only affect RGB
+/// images. Results are clipped to `[0, max_value]`, which is 255 for the raw datasets and has
+/// to be lowered for normalized ones.
+pub struct ColorJitter {
+    brightness: f32,
+    contrast: f32,
+    saturation: f32,
+    hue: f32,
+    max_value: f32,
+}
+
+impl ColorJitter {
+    pub fn new(brightness: f32, contrast: f32, saturation: f32, hue: f32) -> ColorJitter {
+        ColorJitter {
+            brightness,
+            contrast,
+            saturation,
+            hue,
+            max_value: 255.,
+        }
+    }
+
+    pub fn max_value(mut self, max_value: f32) -> ColorJitter {
+        self.max_value = max_value;
+        self
+    }
+}
+
+/// Luminance of an RGB image, or the single channel of a grayscale one.
+fn luminance(image: &Array3<f32>) -> Array2<f32> {
+    if image.shape()[0] == 3 {
+        let channel = |c: usize| image.index_axis(Axis(0), c);
+        &channel(0) * 0.299 + &channel(1) * 0.587 + &channel(2) * 0.114
+    } else {
+        image.index_axis(Axis(0), 0).to_owned()
+    }
+}
+
+impl Transform for ColorJitter {
+    fn apply(&self, mut image: Array3<f32>, rng: &mut StdRng) -> Array3<f32> {
+        let factor = |rng: &mut StdRng, x: f32| uniform(rng, (1. - x).max(0.), 1. + x);
+        let brightness = factor(rng, self.brightness);
+        let contrast = factor(rng, self.contrast);
+        let saturation = factor(rng, self.saturation);
+        let hue = uniform(rng, -self.hue, self.hue);
+
+        image.mapv_inplace(|x| x * brightness);
+        let mean = luminance(&image).mean().unwrap_or(0.);
+        image.mapv_inplace(|x| mean + contrast * (x - mean));
+        if image.shape()[0] == 3 {
+            let gray = luminance(&image);
+            for mut channel in image.outer_iter_mut() {
+                Zip::from(&mut channel)
+                    .and(&gray)
+                    .apply(|x, &g| *x = g + saturation * (*x - g));
+            }
+            if hue != 0. {
+                rotate_hue(&mut image, hue * 2. * std::f32::consts::PI);
+            }
+        }
+        let max_value = self.max_value;
+        image.mapv_inplace(|x| x.max(0.).min(max_value));
+        image
+    }
+}
+
+/// Rotate the chroma of an RGB image by `angle` radians in YIQ space.
+fn rotate_hue(image: &mut Array3<f32>, angle: f32) {
+    let (sin, cos) = angle.sin_cos();
+    let (_, rows, cols) = image.dim();
+    for r in 0..rows {
+        for c in 0..cols {
+            let (red, green, blue) = (image[[0, r, c]], image[[1, r, c]], image[[2, r, c]]);
+            let y = 0.299 * red + 0.587 * green + 0.114 * blue;
+            let i = 0.596 * red - 0.274 * green - 0.322 * blue;
+            let q = 0.211 * red - 0.523 * green + 0.312 * blue;
+            let (i, q) = (cos * i - sin * q, sin * i + cos * q);
+            image[[0, r, c]] = y + 0.956 * i + 0.621 * q;
+            image[[1, r, c]] = y - 0.272 * i - 0.647 * q;
+            image[[2, r, c]] = y - 1.106 * i + 1.703 * q;
+        }
+    }
+}
+
+/// Sets a `size` x `size` square around a random pixel to zero, the square may reach over the
+/// image border (DeVries and Taylor, 2017).
+pub struct Cutout {
+    size: usize,
+}
+
+impl Cutout {
+    pub fn new(size: usize) -> Cutout {
+        Cutout { size }
+    }
+}
+
+impl Transform for Cutout {
+    fn apply(&self, mut image: Array3<f32>, rng: &mut StdRng) -> Array3<f32> {
+        let (_, rows, cols) = image.dim();
+        if rows == 0 || cols == 0 {
+            return image;
+        }
+        let (row, col) = (rng.gen_range(0..rows), rng.gen_range(0..cols));
+        let half = self.size / 2;
+        let (r0, r1) = (row.saturating_sub(half), (row + self.size - half).min(rows));
+        let (c0, c1) = (col.saturating_sub(half), (col + self.size - half).min(cols));
+        image.slice_mut(s![.., r0..r1, c0..c1]).fill(0.);
+        image
+    }
+}
+
+/// With probability `p` replaces a random rectangle by `value` (Zhong et al., 2017).
+///
+/// The rectangle covers a fraction of the image drawn from `[0.02, 0.33]` and has an aspect
+/// ratio drawn log-uniformly from `[0.3, 3.3]`; rectangles not fitting the image are retried
+/// up to ten times.
+pub struct RandomErasing {
+    p: f32,
+    scale: (f32, f32),
+    ratio: (f32, f32),
+    value: f32,
+}
+
+impl RandomErasing {
+    pub fn new(p: f32) -> RandomErasing {
+        RandomErasing {
+            p,
+            scale: (0.02, 0.33),
+            ratio: (0.3, 3.3),
+            value: 0.,
+        }
+    }
+
+    pub fn scale(mut self, min: f32, max: f32) -> RandomErasing {
+        self.scale = (min, max);
+        self
+    }
+
+    pub fn ratio(mut self, min: f32, max: f32) -> RandomErasing {
+        self.ratio = (min, max);
+        self
+    }
+
+    pub fn value(mut self, value: f32) -> RandomErasing {
+        self.value = value;
+        self
+    }
+}
+
+impl Transform for RandomErasing {
+    fn apply(&self, mut image: Array3<f32>, rng: &mut StdRng) -> Array3<f32> {
+        if rng.gen::<f32>() >= self.p {
+            return image;
+        }
+        let (_, rows, cols) = image.dim();
+        let area = (rows * cols) as f32;
+        for _ in 0..10 {
+            let target = area * uniform(rng, self.scale.0, self.scale.1);
+            let ratio = uniform(rng, self.ratio.0.ln(), self.ratio.1.ln()).exp();
+            let h = (target * ratio).sqrt().round() as usize;
+            let w = (target / ratio).sqrt().round() as usize;
+            if h == 0 || w == 0 || h > rows || w > cols {
+                continue;
+            }
+            let (top, left) = (rng.gen_range(0..=rows - h), rng.gen_range(0..=cols - w));
+            image
+                .slice_mut(s![.., top..top + h, left..left + w])
+                .fill(self.value);
+            break;
+        }
+        image
+    }
+}
+
+/// Adds independent Gaussian noise with standard deviation `std` to every pixel.
+pub struct GaussianNoise {
+    std: f32,
+}
+
+impl GaussianNoise {
+    pub fn new(std: f32) -> GaussianNoise {
+        GaussianNoise { std }
+    }
+}
+
+impl Transform for GaussianNoise {
+    fn apply(&self, mut image: Array3<f32>, rng: &mut StdRng) -> Array3<f32> {
+        let std = self.std;
+        image.mapv_inplace(|x| x + std * standard_normal(rng) as f32);
+        image
+    }
+}
+
+/// Elastic distortion of Simard et al. (2003): every pixel is moved by a random displacement
+/// field that is smoothed with a Gaussian of standard deviation `sigma` and scaled by `alpha`
+/// pixels, the image is resampled bilinearly. All channels share the same field.
+///
+/// `ElasticDistortion::new(34., 4.)` are the settings of the paper for 28x28 MNIST digits.
+pub struct ElasticDistortion {
+    alpha: f32,
+    sigma: f32,
+}
+
+impl ElasticDistortion {
+    pub fn new(alpha: f32, sigma: f32) -> ElasticDistortion {
+        assert!(sigma > 0., "Sigma has to be positive, got {}.", sigma);
+        ElasticDistortion { alpha, sigma }
+    }
+
+    /// Distort a single `(rows, cols)` image, e.g. one of `mnist::Data::trn_img`.
+    pub fn distort(&self, image: &ArrayView2<f32>, rng: &mut StdRng) -> Array2<f32> {
+        let image = image.to_owned().insert_axis(Axis(0));
+        self.apply(image, rng).index_axis_move(Axis(0), 0)
+    }
+
+    /// Smoothed row and column displacements.
+    fn displacement(&self, dim: (usize, usize), rng: &mut StdRng) -> (Array2<f32>, Array2<f32>) {
+        let mut field = || {
+            let noise = Array2::from_shape_simple_fn(dim, || uniform(rng, -1., 1.));
+            gaussian_blur(&noise, self.sigma) * self.alpha
+        };
+        let dy = field();
+        let dx = field();
+        (dy, dx)
+    }
+}
+
+impl Transform for ElasticDistortion {
+    fn apply(&self, image: Array3<f32>, rng: &mut StdRng) -> Array3<f32> {
+        let (_, rows, cols) = image.dim();
+        let (dy, dx) = self.displacement((rows, cols), rng);
+        let mut out = Array3::zeros(image.dim());
+        for (mut out_channel, channel) in out.outer_iter_mut().zip(image.outer_iter()) {
+            for ((r, c), value) in out_channel.indexed_iter_mut() {
+                *value = bilinear(&channel, r as f32 + dy[[r, c]], c as f32 + dx[[r, c]], 0.);
+            }
+        }
+        out
+    }
+}
+
+/// Separable Gaussian filter truncated at three standard deviations, treating pixels outside
+/// of the image as zero.
+fn gaussian_blur(image: &Array2<f32>, sigma: f32) -> Array2<f32> {
+    let radius = (3. * sigma).ceil() as isize;
+    let kernel: Vec<f32> = (-radius..=radius)
+        .map(|x| (-((x * x) as f32) / (2. * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = kernel.iter().sum();
+    let kernel: Vec<f32> = kernel.iter().map(|k| k / total).collect();
+    let convolve = |image: &Array2<f32>, axis: usize| {
+        let dim = image.dim();
+        Array2::from_shape_fn(dim, |(r, c)| {
+            let (pos, len) = if axis == 0 { (r, dim.0) } else { (c, dim.1) };
+            kernel
+                .iter()
+                .enumerate()
+                .map(|(k, weight)| {
+                    let i = pos as isize + k as isize - radius;
+                    if i < 0 || i >= len as isize {
+                        0.
+                    } else if axis == 0 {
+                        weight * image[[i as usize, c]]
+                    } else {
+                        weight * image[[r, i as usize]]
+                    }
+                })
+                .sum()
+        })
+    };
+    convolve(&convolve(image, 0), 1)
+}
+
+/// Augments a whole NCHW batch together with its one-hot labels, e.g. by mixing samples.
+pub trait BatchTransform: Sync {
+    fn apply_batch(
+        &self,
+        images: Array4<f32>,
+        labels: Array2<f32>,
+        rng: &mut StdRng,
+    ) -> (Array4<f32>, Array2<f32>);
+}
+
+fn random_permutation(len: usize, rng: &mut StdRng) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..len).collect();
+    order.shuffle(rng);
+    order
+}
+
+/// Replaces every sample by a convex combination `lambda * x_i + (1 - lambda) * x_j` with a
+/// random partner `j` of the same batch, the labels are mixed the same way (Zhang et al.,
+/// 2018). `lambda` is drawn once per batch from Beta(alpha, alpha).
+pub struct MixUp {
+    alpha: f64,
+}
+
+impl MixUp {
+    pub fn new(alpha: f64) -> MixUp {
+        assert!(alpha > 0., "Alpha has to be positive, got {}.", alpha);
+        MixUp { alpha }
+    }
+}
+
+impl BatchTransform for MixUp {
+    fn apply_batch(
+        &self,
+        images: Array4<f32>,
+        labels: Array2<f32>,
+        rng: &mut StdRng,
+    ) -> (Array4<f32>, Array2<f32>) {
+        let lambda = beta(rng, self.alpha, self.alpha) as f32;
+        let order = random_permutation(labels.nrows(), rng);
+        let images = images.clone() * lambda + images.select(Axis(0), &order) * (1. - lambda);
+        let labels = labels.clone() * lambda + labels.select(Axis(0), &order) * (1. - lambda);
+        (images, labels)
+    }
+}
+
+/// Pastes a random rectangle of a random partner of the same batch into every sample, the
+/// labels are mixed in proportion to the pasted area (Yun et al., 2019).
+///
+/// The rectangle covers a fraction `1 - lambda` of the image, with `lambda` drawn once per
+/// batch from Beta(alpha, alpha), and is clipped at the image border.
+pub struct CutMix {
+    alpha: f64,
+}
+
+impl CutMix {
+    pub fn new(alpha: f64) -> CutMix {
+        assert!(alpha > 0., "Alpha has to be positive, got {}.", alpha);
+        CutMix { alpha }
+    }
+}
+
+impl BatchTransform for CutMix {
+    fn apply_batch(
+        &self,
+        mut images: Array4<f32>,
+        labels: Array2<f32>,
+        rng: &mut StdRng,
+    ) -> (Array4<f32>, Array2<f32>) {
+        let (_, _, rows, cols) = images.dim();
+        let lambda = beta(rng, self.alpha, self.alpha);
+        let order = random_permutation(labels.nrows(), rng);
+        let cut = (1. - lambda).sqrt();
+        let (h, w) = (
+            (rows as f64 * cut).round() as usize,
+            (cols as f64 * cut).round() as usize,
+        );
+        let (cy, cx) = (rng.gen_range(0..rows.max(1)), rng.gen_range(0..cols.max(1)));
+        let (r0, r1) = (cy.saturating_sub(h / 2), (cy + h - h / 2).min(rows));
+        let (c0, c1) = (cx.saturating_sub(w / 2), (cx + w - w / 2).min(cols));
+        let partners = images.select(Axis(0), &order);
+        images
+            .slice_mut(s![.., .., r0..r1, c0..c1])
+            .assign(&partners.slice(s![.., .., r0..r1, c0..c1]));
+        // The share of the original image after clipping.
+        let lambda = 1. - ((r1 - r0) * (c1 - c0)) as f32 / (rows * cols).max(1) as f32;
+        let labels = labels.clone() * lambda + labels.select(Axis(0), &order) * (1. - lambda);
+        (images, labels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch() -> Array4<f32> {
+        Array4::from_shape_fn((6, 3, 8, 8), |(n, c, r, col)| {
+            (n * 192 + c * 64 + r * 8 + col) as f32 / 1152.
+        })
+    }
+
+    fn max_difference(a: &Array3<f32>, b: &ArrayView3<f32>) -> f32 {
+        assert_eq!(a.dim(), b.dim());
+        a.iter()
+            .zip(b)
+            .fold(0., |max, (x, y)| max.max((x - y).abs()))
+    }
+
+    #[test]
+    fn seeded_batches_are_deterministic() {
+        let images = batch();
+        let transforms: Vec<Box<dyn Transform>> = vec![
+            Box::new(RandomCrop::new(8, 8, 2)),
+            Box::new(RandomHorizontalFlip::new(0.5)),
+            Box::new(RandomRotation::new(30.)),
+            Box::new(
+                RandomAffine::new()
+                    .translate(0.2, 0.2)
+                    .scale(0.8, 1.2)
+                    .shear(10.),
+            ),
+            Box::new(ColorJitter::new(0.4, 0.4, 0.4, 0.1)),
+            Box::new(Cutout::new(3)),
+            Box::new(RandomErasing::new(1.)),
+            Box::new(GaussianNoise::new(0.1)),
+            Box::new(ElasticDistortion::new(8., 2.)),
+        ];
+        for transform in &transforms {
+            let first = transform.apply_nchw(&images, 7);
+            assert_eq!(first.dim(), images.dim());
+            assert_eq!(first, transform.apply_nchw(&images, 7));
+            assert_ne!(first, transform.apply_nchw(&images, 8));
+        }
+        let gray = images.index_axis(Axis(1), 0).to_owned();
+        let augment = Compose::new(transforms);
+        assert_eq!(augment.apply_nhw(&gray, 3), augment.apply_nhw(&gray, 3));
+    }
+
+    #[test]
+    fn image_streams_are_distinct() {
+        let mut first = std::collections::HashSet::new();
+        for seed in 0..64 {
+            for index in 0..64 {
+                assert!(first.insert(image_rng(seed, index).gen::<u64>()));
+            }
+        }
+        // Pairs that collided when the index was only XORed into the seed
+        let golden = 0x9E37_79B9_7F4A_7C15u64;
+        let a = image_rng(golden, 0).gen::<u64>();
+        let b = image_rng(0, 1).gen::<u64>();
+        assert_ne!(a, b);
+        assert_ne!(image_rng(3, 2).gen::<u64>(), image_rng(2, 3).gen::<u64>());
+    }
+
+    #[test]
+    fn horizontal_flip() {
+        let images = batch();
+        let mut rng = StdRng::seed_from_u64(0);
+        let image = images.index_axis(Axis(0), 0).to_owned();
+        let flipped = RandomHorizontalFlip::new(1.).apply(image.clone(), &mut rng);
+        assert_eq!(flipped, image.slice(s![.., .., ..;-1]));
+        assert!(flipped.is_standard_layout());
+        assert_eq!(
+            RandomHorizontalFlip::new(0.).apply(image.clone(), &mut rng),
+            image
+        );
+        assert_eq!(RandomHorizontalFlip::new(0.).apply_nchw(&images, 1), images);
+    }
+
+    #[test]
+    fn crop_with_padding() {
+        let image = batch().index_axis(Axis(0), 1).to_owned();
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            RandomCrop::new(8, 8, 0).apply(image.clone(), &mut rng),
+            image
+        );
+
+        let mut padded = Array3::zeros((3, 12, 12));
+        padded.slice_mut(s![.., 2..10, 2..10]).assign(&image);
+        let mut offsets = std::collections::HashSet::new();
+        for _ in 0..200 {
+            let crop = RandomCrop::new(8, 8, 2).apply(image.clone(), &mut rng);
+            assert_eq!(crop.dim(), (3, 8, 8));
+            // Every crop is one of the 5 x 5 windows of the zero padded image.
+            let offset = (0..5)
+                .flat_map(|top| (0..5).map(move |left| (top, left)))
+                .find(|&(top, left)| crop == padded.slice(s![.., top..top + 8, left..left + 8]))
+                .expect("Crop is not a window of the padded image.");
+            offsets.insert(offset);
+        }
+        assert_eq!(offsets.len(), 25);
+        assert_eq!(
+            RandomCrop::new(5, 3, 0).apply(image, &mut rng).dim(),
+            (3, 5, 3)
+        );
+    }
+
+    #[test]
+    fn rotation() {
+        let image = batch().index_axis(Axis(0), 2).to_owned();
+        let mut rng = StdRng::seed_from_u64(0);
+        let unchanged = RandomRotation::new(0.).apply(image.clone(), &mut rng);
+        assert!(max_difference(&unchanged, &image.view()) < 1e-6);
+
+        // A quarter turn counter-clockwise moves the last column to the first row.
+        let quarter = warp_affine(&image, [[0., -1.], [1., 0.]], (0., 0.));
+        let expected = image.slice(s![.., .., ..;-1]).permuted_axes([0, 2, 1]);
+        assert!(max_difference(&quarter, &expected.view()) < 1e-5);
+
+        // Rotations only interpolate between pixels and the zero fill.
+        let (low, high) = image.fold((0f32, 0f32), |(l, h), &x| (l.min(x), h.max(x)));
+        for _ in 0..10 {
+            let rotated = RandomRotation::new(45.).apply(image.clone(), &mut rng);
+            assert_eq!(rotated.dim(), image.dim());
+            assert!(rotated.iter().all(|&x| x >= low - 1e-6 && x <= high + 1e-6));
+        }
+    }
+
+    #[test]
+    fn elastic_distortion() {
+        let image = batch()
+            .index_axis(Axis(0), 3)
+            .index_axis(Axis(0), 0)
+            .to_owned();
+        let distortion = ElasticDistortion::new(0., 2.);
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(distortion.distort(&image.view(), &mut rng), image);
+
+        let distortion = ElasticDistortion::new(6., 2.);
+        let distorted = distortion.distort(&image.view(), &mut StdRng::seed_from_u64(4));
+        let applied = distortion.apply(
+            image.clone().insert_axis(Axis(0)),
+            &mut StdRng::seed_from_u64(4),
+        );
+        assert_eq!(distorted, applied.index_axis(Axis(0), 0));
+        assert_ne!(distorted, image);
+    }
+
+    /// 8 constant NCHW images, image `i` is filled with `i` and has the one-hot label `i`.
+    fn constant_batch() -> (Array4<f32>, Array2<f32>) {
+        let images = Array4::from_shape_fn((8, 2, 6, 5), |(n, _, _, _)| n as f32);
+        (images, Array2::eye(8))
+    }
+
+    #[test]
+    fn mixup() {
+        let (images, labels) = constant_batch();
+        let mixup = MixUp::new(0.4);
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (mixed, soft) = mixup.apply_batch(images.clone(), labels.clone(), &mut rng);
+            assert_eq!(mixed.dim(), images.dim());
+            for (image, label) in mixed.outer_iter().zip(soft.outer_iter()) {
+                assert!((label.sum() - 1.).abs() < 1e-6);
+                assert!(label.iter().all(|&l| l >= 0.));
+                // Every pixel is the same convex combination of two images as the label.
+                let expected: f32 = label.iter().enumerate().map(|(c, l)| c as f32 * l).sum();
+                assert!(image.iter().all(|&x| (x - expected).abs() < 1e-5));
+            }
+            let mut rng = StdRng::seed_from_u64(seed);
+            assert_eq!(
+                (mixed, soft),
+                mixup.apply_batch(images.clone(), labels.clone(), &mut rng)
+            );
+        }
+    }
+
+    #[test]
+    fn cutmix() {
+        let (images, labels) = constant_batch();
+        let cutmix = CutMix::new(1.);
+        let mut pasted = false;
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (mixed, soft) = cutmix.apply_batch(images.clone(), labels.clone(), &mut rng);
+            assert_eq!(mixed.dim(), images.dim());
+            for (i, (image, label)) in mixed.outer_iter().zip(soft.outer_iter()).enumerate() {
+                assert!((label.sum() - 1.).abs() < 1e-6);
+                // The label of every image is the share of its pixels.
+                for (c, &l) in label.iter().enumerate() {
+                    let share = image.iter().filter(|&&x| x == c as f32).count() as f32
+                        / image.len() as f32;
+                    assert!((share - l).abs() < 1e-6);
+                }
+                pasted |= label[i] < 1.;
+            }
+            let mut rng = StdRng::seed_from_u64(seed);
+            assert_eq!(
+                (mixed, soft),
+                cutmix.apply_batch(images.clone(), labels.clone(), &mut rng)
+            );
+        }
+        assert!(pasted);
+    }
+
+    #[test]
+    #[should_panic(expected = "Alpha has to be positive, got 0.")]
+    fn mixup_rejects_zero_alpha() {
+        MixUp::new(0.);
+    }
+
+    #[test]
+    #[should_panic(expected = "Alpha has to be positive, got -1.")]
+    fn cutmix_rejects_negative_alpha() {
+        CutMix::new(-1.);
+    }
+}
@@ -0,0 +1,19 @@
+use std::path::Path;
+
+use crate::download_helper::downloader;
+
+const BASE_URL: &str = "https://www.csie.ntu.edu.tw/~cjlin/libsvmtools/datasets/multiclass";
+const ARCHIVES_TO_DOWNLOAD: &[&str] = &["usps.bz2", "usps.t.bz2"];
+
+pub fn download_and_extract(base_path: &str) -> Result<(), String> {
+    println!("Attempting to download and extract USPS...");
+    downloader::download(
+        base_path,
+        BASE_URL.to_string(),
+        ARCHIVES_TO_DOWNLOAD.to_vec(),
+    )?;
+    for archive in ARCHIVES_TO_DOWNLOAD {
+        downloader::extract_bz2(&Path::new(base_path).join(archive))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,5 @@
+pub mod usps_builder;
+pub use usps_builder::usps;
+
+#[cfg(feature = "download")]
+mod download;
@@ -0,0 +1,129 @@
+use ndarray::{Array2, Array3};
+
+use std::path::Path;
+
+#[cfg(feature = "download")]
+use super::download;
+pub use crate::mnist_datasets::mnist_builder::Data;
+use crate::resize::{resize, Interpolation};
+use crate::{image_folder, libsvm};
+
+static TRN_FILENAME: &str = "usps";
+static TST_FILENAME: &str = "usps.t";
+static TRN_LEN: usize = 7291;
+static TST_LEN: usize = 2007;
+static CLASSES: usize = 10;
+static ROWS: usize = 16;
+static COLS: usize = 16;
+
+/// Read a USPS file of the LIBSVM repository, which stores `<digit + 1>` as label and the
+/// pixels in [-1, 1]. Pixels are mapped to [0, 255] like MNIST.
+fn read_usps(path: &Path, expected_length: usize) -> (Array3<f32>, Vec<usize>) {
+    let data = libsvm::load(path)
+        .and_then(|data| data.with_n_features(ROWS * COLS))
+        .unwrap();
+    assert!(
+        data.len() == expected_length,
+        "Expected data set length of {} got {}.",
+        expected_length,
+        data.len()
+    );
+    let img = data
+        .to_dense()
+        .mapv(|x| ((x + 1.) * 127.5) as f32)
+        .into_shape((expected_length, ROWS, COLS))
+        .unwrap();
+    let labels = data
+        .labels
+        .iter()
+        .map(|&l| {
+            assert!(
+                l.fract() == 0. && (1. ..=CLASSES as f64).contains(&l),
+                "USPS label {} is not in 1..={}.",
+                l,
+                CLASSES
+            );
+            l as usize - 1
+        })
+        .collect();
+    (img, labels)
+}
+
+fn get_data(base_path: &str, size: Option<(usize, usize)>, normalized: bool) -> Data {
+    let (mut trn_img, trn_labels) = read_usps(&Path::new(base_path).join(TRN_FILENAME), TRN_LEN);
+    let (mut tst_img, tst_labels) = read_usps(&Path::new(base_path).join(TST_FILENAME), TST_LEN);
+    if let Some((rows, cols)) = size {
+        trn_img = resize(&trn_img, rows, cols, Interpolation::Bilinear);
+        tst_img = resize(&tst_img, rows, cols, Interpolation::Bilinear);
+    }
+    if normalized {
+        trn_img.mapv_inplace(|x| x / 256.);
+        tst_img.mapv_inplace(|x| x / 256.);
+    }
+    let trn_lbl: Array2<f32> = image_folder::one_hot(&trn_labels, CLASSES);
+    let tst_lbl: Array2<f32> = image_folder::one_hot(&tst_labels, CLASSES);
+    Data {
+        trn_img,
+        trn_lbl,
+        tst_img,
+        tst_lbl,
+    }
+}
+
+pub mod usps {
+    pub use super::Data;
+    static BASE_PATH: &str = "data/usps/";
+    pub fn new() -> Data {
+        super::get_data(BASE_PATH, None, false)
+    }
+    pub fn new_normalized() -> Data {
+        super::get_data(BASE_PATH, None, true)
+    }
+    /// Resize the 16 x 16 digits, e.g. to 28 x 28 for MNIST to USPS domain adaptation.
+    pub fn new_resized(rows: usize, cols: usize, normalized: bool) -> Data {
+        super::get_data(BASE_PATH, Some((rows, cols)), normalized)
+    }
+
+    #[cfg(feature = "download")]
+    pub fn download_and_extract() {
+        super::download::download_and_extract(BASE_PATH).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Read `content` as a USPS file of `len` samples.
+    fn read(name: &str, content: &str, len: usize) -> (Array3<f32>, Vec<usize>) {
+        let path = std::env::temp_dir().join(format!("usps-{}-{}", name, std::process::id()));
+        fs::write(&path, content).unwrap();
+        let result = std::panic::catch_unwind(|| read_usps(&path, len));
+        fs::remove_file(&path).unwrap();
+        result.unwrap_or_else(|e| std::panic::resume_unwind(e))
+    }
+
+    #[test]
+    fn labels_and_pixels() {
+        let (img, labels) = read("valid", "1 1:-1 2:1\n10 256:0\n", 2);
+        assert_eq!(labels, vec![0, 9]);
+        assert_eq!(img.dim(), (2, ROWS, COLS));
+        assert_eq!(img[[0, 0, 0]], 0.);
+        assert_eq!(img[[0, 0, 1]], 255.);
+        assert_eq!(img[[0, 0, 2]], 127.5);
+        assert_eq!(img[[1, 15, 15]], 127.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "USPS label 0 is not in 1..=10.")]
+    fn rejects_label_zero() {
+        read("zero", "1 1:0\n0 1:0\n", 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "USPS label 11 is not in 1..=10.")]
+    fn rejects_label_eleven() {
+        read("eleven", "11 1:0\n", 1);
+    }
+}